@@ -0,0 +1,25 @@
+CREATE TABLE albums
+(
+    id             TEXT PRIMARY KEY NOT NULL,
+    title          TEXT NOT NULL,
+    description    TEXT,
+    position       INTEGER NOT NULL,
+    cover_photo_id TEXT REFERENCES photos ( id ) ON DELETE SET NULL,
+    created_at     TEXT NOT NULL,
+    updated_at     TEXT NOT NULL
+);
+
+CREATE TABLE photos
+(
+    id          TEXT PRIMARY KEY NOT NULL,
+    album_id    TEXT REFERENCES albums ( id ) ON DELETE SET NULL,
+    title       TEXT NOT NULL,
+    description TEXT,
+    position    INTEGER NOT NULL,
+    taken_at    TEXT,
+    created_at  TEXT NOT NULL,
+    updated_at  TEXT NOT NULL
+);
+
+CREATE INDEX albums_position ON albums ( position, created_at );
+CREATE INDEX photos_album_id_position ON photos ( album_id, position, created_at );
@@ -0,0 +1,4 @@
+ALTER TABLE photos ADD COLUMN file_name TEXT;
+ALTER TABLE photos ADD COLUMN mime_type TEXT;
+ALTER TABLE photos ADD COLUMN size_bytes INTEGER;
+ALTER TABLE photos ADD COLUMN original_name TEXT;
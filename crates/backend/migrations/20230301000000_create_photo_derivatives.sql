@@ -0,0 +1,11 @@
+CREATE TABLE photo_derivatives
+(
+    photo_id   TEXT NOT NULL REFERENCES photos ( id ) ON DELETE CASCADE,
+    name       TEXT NOT NULL,
+    file_name  TEXT NOT NULL,
+    mime_type  TEXT NOT NULL,
+    width      INTEGER NOT NULL,
+    height     INTEGER NOT NULL,
+    size_bytes INTEGER NOT NULL,
+    PRIMARY KEY ( photo_id, file_name )
+);
@@ -0,0 +1,13 @@
+ALTER TABLE photos ADD COLUMN camera_make TEXT;
+ALTER TABLE photos ADD COLUMN camera_model TEXT;
+ALTER TABLE photos ADD COLUMN lens_model TEXT;
+ALTER TABLE photos ADD COLUMN focal_length_mm REAL;
+ALTER TABLE photos ADD COLUMN f_number REAL;
+ALTER TABLE photos ADD COLUMN exposure_time_secs REAL;
+ALTER TABLE photos ADD COLUMN iso INTEGER;
+ALTER TABLE photos ADD COLUMN metadata_taken_at TEXT;
+ALTER TABLE photos ADD COLUMN gps_latitude REAL;
+ALTER TABLE photos ADD COLUMN gps_longitude REAL;
+ALTER TABLE photos ADD COLUMN gps_altitude_m REAL;
+ALTER TABLE photos ADD COLUMN orientation INTEGER;
+ALTER TABLE photos ADD COLUMN rating INTEGER;
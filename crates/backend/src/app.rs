@@ -0,0 +1,469 @@
+//! Backend application: the api, the static and assets files, the SSR renderer or the CSR index fallback, and the
+//! metrics and tracing layers, built into a router that can be served on any listener.
+
+use crate::{
+    health::{self, HealthChecks},
+    services::{errors::ApiError, image_negotiation::ImageNegotiationLayer, routes},
+    tls::TlsIncoming,
+    Result,
+};
+
+use color_eyre::eyre::eyre;
+
+use monitoring::{logger, prometheus};
+use rustls::ServerConfig;
+use settings::RuntimeEnvironmentType;
+
+#[cfg( feature = "ssr" )]
+use axum::{http, response::Html};
+use axum::{
+    body::Body,
+    extract::State,
+    handler::Handler,
+    http::Request,
+    response::IntoResponse,
+    routing::{any, get, get_service},
+    Router,
+};
+use std::{future::Future, net::TcpListener, sync::Arc, time::Duration};
+use tokio::sync::Notify;
+
+#[cfg( feature = "ssr" )]
+use futures::future;
+#[cfg( not( feature = "ssr" ) )]
+use tower::ServiceExt;
+use tower_http::{
+    compression::CompressionLayer,
+    services::{ServeDir, ServeFile},
+};
+
+/// Backend application, ready to be served.
+pub struct App
+{
+    router: Router,
+}
+
+impl App
+{
+    /// Builder of an app serving the api with the given state, and the files of the static and assets directories.
+    #[must_use]
+    pub fn builder( api_state: routes::ApiState, static_dir: &str, assets_dir: &str ) -> AppBuilder
+    {
+        AppBuilder {
+            api_state,
+            static_dir: static_dir.to_owned(),
+            assets_dir: assets_dir.to_owned(),
+            run_env: RuntimeEnvironmentType::Development,
+            serve_metrics: true,
+            routes: Router::new(),
+            health_checks: HealthChecks::default(),
+        }
+    }
+
+    /// Router of the app, e.g. to nest it in another router.
+    pub fn into_router( self ) -> Router { self.router }
+
+    /// Serves the app on a bound listener until the server fails.
+    ///
+    /// # Errors
+    ///
+    /// If the listener can't be used or the server fails.
+    pub async fn serve( self, listener: TcpListener ) -> Result<()>
+    {
+        axum::Server::from_tcp( listener )?
+            .serve( self.router.into_make_service() )
+            .await?;
+
+        Ok( () )
+    }
+
+    /// Serves the app on a bound listener until the shutdown signal completes, then stops accepting connections and
+    /// waits for the in-flight requests, e.g. streamed SSR pages and uploads, up to a timeout.
+    ///
+    /// # Errors
+    ///
+    /// If the listener can't be used, the server fails or the in-flight requests don't finish before the timeout.
+    pub async fn serve_with_shutdown(
+        self,
+        listener: TcpListener,
+        signal: impl Future<Output = ()> + Send + 'static,
+        timeout: Duration,
+    ) -> Result<()>
+    {
+        let ( graceful_signal, is_shutting_down ) = notify_on( signal );
+        let server = axum::Server::from_tcp( listener )?
+            .serve( self.router.into_make_service() )
+            .with_graceful_shutdown( graceful_signal );
+
+        drain( server, &is_shutting_down, timeout ).await
+    }
+
+    /// Same as [`App::serve_with_shutdown`] with TLS, see [`crate::tls::server_config`].
+    ///
+    /// # Errors
+    ///
+    /// If the listener can't be used, the server fails or the in-flight requests don't finish before the timeout.
+    pub async fn serve_tls_with_shutdown(
+        self,
+        listener: TcpListener,
+        tls_config: Arc<ServerConfig>,
+        signal: impl Future<Output = ()> + Send + 'static,
+        timeout: Duration,
+    ) -> Result<()>
+    {
+        let ( graceful_signal, is_shutting_down ) = notify_on( signal );
+        let server = axum::Server::builder( TlsIncoming::new( listener, tls_config )? )
+            .serve( self.router.into_make_service() )
+            .with_graceful_shutdown( graceful_signal );
+
+        drain( server, &is_shutting_down, timeout ).await
+    }
+}
+
+/// Shutdown signal for hyper, notifying once it completes.
+fn notify_on( signal: impl Future<Output = ()> + Send + 'static ) -> ( impl Future<Output = ()>, Arc<Notify> )
+{
+    let is_shutting_down = Arc::new( Notify::new() );
+    let graceful_signal = {
+        let is_shutting_down = is_shutting_down.clone();
+
+        async move {
+            signal.await;
+            is_shutting_down.notify_one();
+        }
+    };
+
+    ( graceful_signal, is_shutting_down )
+}
+
+/// Runs a server until it stops, or until the timeout once it is shutting down.
+async fn drain(
+    server: impl Future<Output = hyper::Result<()>>,
+    is_shutting_down: &Notify,
+    timeout: Duration,
+) -> Result<()>
+{
+    tokio::pin!( server );
+
+    tokio::select! {
+        result = &mut server => return Ok( result? ),
+        () = is_shutting_down.notified() => (),
+    }
+
+    match tokio::time::timeout( timeout, server ).await
+    {
+        Ok( result ) => Ok( result? ),
+        Err( _ ) => Err( eyre!( "In-flight requests still running after the {:?} shutdown timeout", timeout ) ),
+    }
+}
+
+/// Completes on `SIGINT` or, on unix, `SIGTERM`, e.g. when a container is stopped.
+///
+/// # Panics
+///
+/// If the signal handlers can't be installed.
+pub async fn shutdown_signal()
+{
+    let interrupt = async {
+        tokio::signal::ctrl_c().await.expect( "Failed to listen for SIGINT" );
+    };
+
+    #[cfg( unix )]
+    let terminate = async {
+        tokio::signal::unix::signal( tokio::signal::unix::SignalKind::terminate() )
+            .expect( "Failed to listen for SIGTERM" )
+            .recv()
+            .await;
+    };
+
+    #[cfg( not( unix ) )]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = interrupt => tracing::info!( "Received SIGINT, shutting down." ),
+        () = terminate => tracing::info!( "Received SIGTERM, shutting down." ),
+    }
+}
+
+/// Builder of an [`App`].
+pub struct AppBuilder
+{
+    api_state:     routes::ApiState,
+    static_dir:    String,
+    assets_dir:    String,
+    run_env:       RuntimeEnvironmentType,
+    serve_metrics: bool,
+    routes:        Router,
+    health_checks: HealthChecks,
+}
+
+impl AppBuilder
+{
+    /// Sets the runtime environment the http metrics are labelled with, `development` by default.
+    #[must_use]
+    pub const fn run_env( mut self, run_env: RuntimeEnvironmentType ) -> Self
+    {
+        self.run_env = run_env;
+        self
+    }
+
+    /// Sets whether the app serves the `/metrics` route, e.g. `false` when the metrics are served on an admin port
+    /// with [`metrics_router`]. `true` by default.
+    #[must_use]
+    pub const fn serve_metrics( mut self, serve_metrics: bool ) -> Self
+    {
+        self.serve_metrics = serve_metrics;
+        self
+    }
+
+    /// Adds routes to the app. They take precedence over the SSR renderer or the CSR index fallback, and go through
+    /// the metrics and tracing layers.
+    #[must_use]
+    pub fn merge( mut self, routes: Router ) -> Self
+    {
+        self.routes = self.routes.merge( routes );
+        self
+    }
+
+    /// Adds a check to the readiness probe, e.g. that the database is reachable. The app already checks the static
+    /// directory and the `index.html` template.
+    #[must_use]
+    pub fn health_check<F, Fut>( mut self, name: &str, check: F ) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.health_checks.register( name, check );
+        self
+    }
+
+    /// Builds the app.
+    ///
+    /// # Errors
+    ///
+    /// If the `index.html` of the static directory can't be read when rendering with SSR.
+    // Async for the SSR template only, kept for both renderings so the callers don't depend on the feature.
+    #[cfg_attr( not( feature = "ssr" ), allow( clippy::unused_async ) )]
+    pub async fn build( mut self ) -> Result<App>
+    {
+        let br_compression = CompressionLayer::new().br( true ).no_gzip().no_deflate();
+        let max_upload_bytes = self.api_state.upload_rules.max_bytes;
+
+        // Uploaded photos directory get service.
+        let serve_photos_dir =
+            get_service( ServeDir::new( self.api_state.photo_storage.dir() ) ).handle_error( handle_error );
+
+        // Api router.
+        let mut app = Router::new()
+            .route( "/api/hello", get( hello ).layer( br_compression.clone() ) )
+            .nest( "/api/albums", routes::albums::router() )
+            .nest( "/api/photos", routes::photos::router( max_upload_bytes ) )
+            .nest_service( routes::photos::PHOTOS_URL_PATH, serve_photos_dir )
+            // The api paths without a route answer a problem, not the SSR page or the CSR index.
+            .route( "/api", any( api_not_found ) )
+            .route( "/api/*path", any( api_not_found ) )
+            .with_state( self.api_state )
+            .merge( self.routes );
+
+        // Robot.txt file get service.
+        let robots_file = get_service( ServeFile::new( format!( "{}/robots.txt", self.assets_dir ) ) )
+            .layer( br_compression.clone() )
+            .handle_error( handle_error );
+
+        // Static files directory get service.
+        let serve_static_dir =
+            get_service( ServeDir::new( &self.static_dir ).precompressed_br() ).handle_error( handle_error );
+
+        // Assets files directory get service, images are served in the best format the client accepts.
+        let serve_assets_dir = get_service( ServeDir::new( &self.assets_dir ).precompressed_br() )
+            .handle_error( handle_error )
+            .layer( ImageNegotiationLayer::new( &self.assets_dir ) );
+
+        // Routes.
+        app = app
+            .route( "/robots.txt", robots_file )
+            .nest_service( "/static", serve_static_dir )
+            .nest_service( "/assets", serve_assets_dir );
+
+        let static_dir = self.static_dir.clone();
+        self.health_checks.register( "static_dir", move || {
+            let static_dir = static_dir.clone();
+
+            async move {
+                if tokio::fs::metadata( &static_dir ).await?.is_dir()
+                {
+                    Ok( () )
+                }
+                else
+                {
+                    Err( eyre!( "{} is not a directory", static_dir ) )
+                }
+            }
+        } );
+
+        #[cfg( feature = "ssr" )]
+        {
+            // Yew render service for SSR.
+            let state = get_yew_render_state( &self.static_dir ).await?;
+
+            // The template is loaded once, checks that it can still wrap the rendered app.
+            let template = state.clone();
+            self.health_checks.register( "index_html", move || future::ready( template.check() ) );
+
+            let renderer = render_yew_app.layer( br_compression ).with_state( state );
+
+            app = app.fallback_service( renderer );
+        }
+
+        #[cfg( not( feature = "ssr" ) )]
+        {
+            // Index file of the CSR app, served for the paths that are not api ones so the frontend router handles
+            // them.
+            let index_path = format!( "{}/index.html", self.static_dir );
+            let index_file = ServeFile::new( &index_path );
+            let spa_fallback = serve_spa_index.layer( br_compression ).with_state( index_file );
+
+            // The index file is read on each request.
+            self.health_checks.register( "index_html", move || {
+                let index_path = index_path.clone();
+
+                async move {
+                    tokio::fs::metadata( &index_path ).await?;
+                    Ok( () )
+                }
+            } );
+
+            app = app.fallback_service( spa_fallback );
+        }
+
+        // Http metrics middleware layer.
+        let mut app = prometheus::middleware_http_metrics( app, &self.run_env.to_string() );
+
+        if self.serve_metrics
+        {
+            app = app.merge( metrics_router() );
+        }
+
+        // Health probes, left out of the http metrics like the metrics route.
+        let app = app.merge( health::router( self.health_checks ) );
+
+        // Http tracing logs middleware layer.
+        let app = logger::middleware_http_tracing( app );
+
+        Ok( App { router: app } )
+    }
+}
+
+/// Router of the `/metrics` route, e.g. to serve it on an admin port.
+pub fn metrics_router() -> Router { Router::new().route( "/metrics", get( routes::metrics::get_metrics ) ) }
+
+#[cfg( feature = "ssr" )]
+#[derive(Clone)]
+struct YewRendererState
+{
+    index_html_before: String,
+    index_html_after:  String,
+}
+
+#[cfg( feature = "ssr" )]
+impl YewRendererState
+{
+    /// Checks that the `<body>` split of the index.html left a document around the rendered app.
+    fn check( &self ) -> Result<()>
+    {
+        let head = self.index_html_before.trim_end_matches( "<body>" ).trim();
+
+        if head.is_empty() || self.index_html_after.trim().is_empty()
+        {
+            return Err( eyre!( "The index.html has nothing before or after its <body>" ) );
+        }
+
+        Ok( () )
+    }
+}
+
+#[cfg( feature = "ssr" )]
+async fn render_yew_app( State( state ): State<YewRendererState>, req: Request<Body> ) -> impl IntoResponse
+{
+    let req_url = req.uri().path().to_string();
+    let req_queries: Vec<( String, String )> = qstring::QString::from( req.uri().query().unwrap_or( "" ) ).into();
+    let response_status = frontend::ResponseStatusHandle::default();
+    let head = frontend::HeadHandle::default();
+
+    let renderer = yew::ServerRenderer::<frontend::ServerApp>::with_props( {
+        let response_status = response_status.clone();
+        let head = head.clone();
+
+        move || frontend::ServerAppProps {
+            request_data: frontend::RequestData {
+                url:     req_url,
+                queries: req_queries,
+            },
+            response_status,
+            head,
+        }
+    } );
+
+    // Render the whole app before answering, so that any component of the page, even a suspended one, can set the
+    // response status and head.
+    let app_html = renderer.render().await;
+
+    let mut status_code = http::StatusCode::OK;
+    let mut headers = http::HeaderMap::new();
+
+    if let Some( response_status ) = response_status.get()
+    {
+        status_code = http::StatusCode::from_u16( response_status.code ).unwrap_or( status_code );
+
+        if let Some( location ) = response_status
+            .location
+            .and_then( |location| http::HeaderValue::from_str( &location ).ok() )
+        {
+            headers.insert( http::header::LOCATION, location );
+        }
+    }
+
+    // Apply the head elements set by the rendered page to the index.html head.
+    let index_html_before = head.get().apply_to_html( &state.index_html_before );
+
+    ( status_code, headers, Html( format!( "{index_html_before}{app_html}{}", state.index_html_after ) ) )
+}
+
+#[cfg( feature = "ssr" )]
+async fn get_yew_render_state( static_dir: &str ) -> Result<YewRendererState>
+{
+    // Get index file.
+    let index_html_s = tokio::fs::read_to_string( format!( "{static_dir}/index.html" ) ).await?;
+    let ( index_html_before, index_html_after ) = index_html_s
+        .split_once( "<body>" )
+        .ok_or_else( || eyre!( "The index.html of {} has no <body>", static_dir ) )?;
+    let mut index_html_before = index_html_before.to_owned();
+    index_html_before.push_str( "<body>" );
+
+    let index_html_after = index_html_after.to_owned();
+
+    let state = YewRendererState {
+        index_html_before,
+        index_html_after,
+    };
+
+    Ok( state )
+}
+
+/// Serves the index file of the CSR app.
+#[cfg( not( feature = "ssr" ) )]
+async fn serve_spa_index( State( index_file ): State<ServeFile>, req: Request<Body> ) -> axum::response::Response
+{
+    match index_file.oneshot( req ).await
+    {
+        Ok( response ) => response.into_response(),
+        Err( err ) => handle_error( err ).await.into_response(),
+    }
+}
+
+/// Answers the errors of the files services, logged as internal errors.
+async fn handle_error( err: std::io::Error ) -> ApiError { ApiError::internal( err ) }
+
+async fn api_not_found() -> ApiError { ApiError::NotFound }
+
+async fn hello() -> impl IntoResponse { "hello from the backend!" }
@@ -1,6 +1,6 @@
 use backend::settings;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use smartstring::alias::String as SmartString;
 
 // Command line arguments interface.
@@ -28,6 +28,23 @@ struct CliArgs
     /// Set the assets files directory
     #[clap( long = "assets-dir", default_value = settings::SERVER.assets_dir().as_str() )]
     assets_dir: SmartString,
+
+    /// Set the metrics admin port. If not set, metrics are served on the main port.
+    #[clap( long = "metrics-port" )]
+    metrics_port: Option<u16>,
+
+    #[clap( subcommand )]
+    command: Option<Command>,
+}
+
+// Command line subcommands. Without a subcommand the server is started.
+#[derive(Subcommand, Debug)]
+enum Command
+{
+    /// Apply the pending database migrations and exit.
+    Migrate,
+    /// Regenerate the derivatives of all the stored photos and exit.
+    RegenerateDerivatives,
 }
 
 fn main() -> backend::Result<()>
@@ -39,16 +56,40 @@ fn main() -> backend::Result<()>
     let cli_args = CliArgs::parse();
 
     // Tracing logs.
-    let ( _maybe_stdio_writer_guard, _maybe_file_writer_guard ) = backend::start_logs( &cli_args.log_level );
+    let ( maybe_stdio_writer_guard, maybe_file_writer_guard ) = backend::start_logs( &cli_args.log_level );
+
+    match cli_args.command
+    {
+        Some( Command::Migrate ) =>
+        {
+            tracing::info!( "Migrating database." );
+            return backend::migrate_database();
+        }
+        Some( Command::RegenerateDerivatives ) =>
+        {
+            tracing::info!( "Regenerating photo derivatives." );
+            return backend::regenerate_derivatives();
+        }
+        None => (),
+    }
 
     tracing::info!( "Starting backend." );
 
-    backend::start_server(
+    let result = backend::start_server(
         &cli_args.addr,
         cli_args.port,
         &cli_args.static_dir,
         &cli_args.assets_dir,
+        cli_args.metrics_port.or( *settings::SERVER.metrics_port() ),
     );
 
-    Ok( () )
+    if let Err( err ) = &result
+    {
+        tracing::error!( "Backend stopped: {:?}", err );
+    }
+
+    // Flush the non-blocking log writers before exiting.
+    drop( ( maybe_stdio_writer_guard, maybe_file_writer_guard ) );
+
+    result
 }
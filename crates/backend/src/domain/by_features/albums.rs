@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// An ordered collection of photos telling a story.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Album
+{
+    pub id:             Uuid,
+    pub title:          String,
+    pub description:    Option<String>,
+    pub position:       u32,
+    pub cover_photo_id: Option<Uuid>,
+    pub created_at:     DateTime<Utc>,
+    pub updated_at:     DateTime<Utc>,
+}
+
+/// Editable fields of an album.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlbumFields
+{
+    pub title:          String,
+    pub description:    Option<String>,
+    pub position:       u32,
+    pub cover_photo_id: Option<Uuid>,
+}
+
+impl Album
+{
+    #[must_use]
+    pub fn new( fields: AlbumFields ) -> Self
+    {
+        let now = Utc::now();
+
+        Self {
+            id:             Uuid::new_v4(),
+            title:          fields.title,
+            description:    fields.description,
+            position:       fields.position,
+            cover_photo_id: fields.cover_photo_id,
+            created_at:     now,
+            updated_at:     now,
+        }
+    }
+
+    /// Replaces the editable fields of the album.
+    pub fn update( &mut self, fields: AlbumFields )
+    {
+        self.title = fields.title;
+        self.description = fields.description;
+        self.position = fields.position;
+        self.cover_photo_id = fields.cover_photo_id;
+        self.updated_at = Utc::now();
+    }
+}
@@ -1 +1,3 @@
-
+// Modules.
+pub mod albums;
+pub mod photos;
@@ -0,0 +1,119 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// A photo, optionally part of an album.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Photo
+{
+    pub id:          Uuid,
+    pub album_id:    Option<Uuid>,
+    pub title:       String,
+    pub description: Option<String>,
+    pub position:    u32,
+    pub taken_at:    Option<DateTime<Utc>>,
+    pub file:        Option<PhotoFile>,
+    pub created_at:  DateTime<Utc>,
+    pub updated_at:  DateTime<Utc>,
+}
+
+/// Original file of a photo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhotoFile
+{
+    /// Name of the file on the photo storage directory.
+    pub file_name:     String,
+    pub mime_type:     String,
+    pub size_bytes:    u64,
+    /// Name of the file as uploaded.
+    pub original_name: Option<String>,
+    /// Resized and re-encoded copies of the file, smallest first.
+    pub derivatives:   Vec<PhotoDerivative>,
+    pub metadata:      PhotoMetadata,
+}
+
+/// Camera metadata of the original file of a photo, read from its EXIF and XMP data.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PhotoMetadata
+{
+    pub camera_make:        Option<String>,
+    pub camera_model:       Option<String>,
+    pub lens_model:         Option<String>,
+    pub focal_length_mm:    Option<f64>,
+    pub f_number:           Option<f64>,
+    pub exposure_time_secs: Option<f64>,
+    pub iso:                Option<u32>,
+    /// Date the photo was taken, in UTC if the camera didn't record its time zone.
+    pub taken_at:           Option<DateTime<Utc>>,
+    pub gps:                Option<GpsPosition>,
+    /// EXIF orientation, from 1 to 8. It is already applied to the derivatives.
+    pub orientation:        Option<u16>,
+    /// XMP rating, from 0 to 5.
+    pub rating:             Option<u8>,
+}
+
+/// Position where a photo was taken, in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GpsPosition
+{
+    pub latitude:   f64,
+    pub longitude:  f64,
+    /// Meters above sea level.
+    pub altitude_m: Option<f64>,
+}
+
+/// Resized and re-encoded copy of the original file of a photo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhotoDerivative
+{
+    /// Name of the size of the derivative, from the storage settings.
+    pub name:       String,
+    /// Name of the file on the photo storage directory.
+    pub file_name:  String,
+    pub mime_type:  String,
+    pub width:      u32,
+    pub height:     u32,
+    pub size_bytes: u64,
+}
+
+/// Editable fields of a photo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhotoFields
+{
+    pub album_id:    Option<Uuid>,
+    pub title:       String,
+    pub description: Option<String>,
+    pub position:    u32,
+    pub taken_at:    Option<DateTime<Utc>>,
+}
+
+impl Photo
+{
+    #[must_use]
+    pub fn new( fields: PhotoFields ) -> Self
+    {
+        let now = Utc::now();
+
+        Self {
+            id:          Uuid::new_v4(),
+            album_id:    fields.album_id,
+            title:       fields.title,
+            description: fields.description,
+            position:    fields.position,
+            taken_at:    fields.taken_at,
+            file:        None,
+            created_at:  now,
+            updated_at:  now,
+        }
+    }
+
+    /// Replaces the editable fields of the photo.
+    pub fn update( &mut self, fields: PhotoFields )
+    {
+        self.album_id = fields.album_id;
+        self.title = fields.title;
+        self.description = fields.description;
+        self.position = fields.position;
+        self.taken_at = fields.taken_at;
+        self.updated_at = Utc::now();
+    }
+}
@@ -1,5 +1,5 @@
 #[path = "../domain/mod.rs"]
-mod domain;
+pub mod domain;
 
 #[path = "../infrastructure/mod.rs"]
-mod infrastructure;
+pub mod infrastructure;
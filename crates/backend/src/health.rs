@@ -0,0 +1,186 @@
+//! Liveness and readiness probes, e.g. for docker-compose and fly.io.
+//!
+//! `/health/live` answers as long as the server serves requests. `/health/ready` runs the checks registered by the
+//! subsystems and answers `503 Service Unavailable` if one of them is down. The errors of the checks are only
+//! logged, the public reports say whether a check is down or timed out.
+
+use crate::Result;
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+use color_eyre::eyre::WrapErr;
+use futures::future::{self, BoxFuture};
+use serde::Serialize;
+use std::{
+    future::Future,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use uuid::Uuid;
+
+/// Maximum duration of a check before it is reported down.
+const CHECK_TIMEOUT: Duration = Duration::from_secs( 2 );
+
+type Check = Arc<dyn Fn() -> BoxFuture<'static, Result<()>> + Send + Sync>;
+
+/// Readiness checks registered by the subsystems, run concurrently on each readiness probe.
+#[derive(Clone, Default)]
+pub struct HealthChecks
+{
+    checks: Vec<( String, Check )>,
+}
+
+impl HealthChecks
+{
+    /// Registers a check, up when it returns `Ok`.
+    pub fn register<F, Fut>( &mut self, name: &str, check: F )
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.checks.push( ( name.to_owned(), Arc::new( move || Box::pin( check() ) ) ) );
+    }
+
+    /// Runs the checks, down if one of them fails or times out.
+    pub async fn run( &self ) -> Readiness
+    {
+        let checks = future::join_all( self.checks.iter().map( |( name, check )| async move {
+            let start = Instant::now();
+            let result = tokio::time::timeout( CHECK_TIMEOUT, check() ).await;
+            let latency_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+            let reason = match result
+            {
+                Ok( Ok( () ) ) => None,
+                Ok( Err( err ) ) =>
+                {
+                    tracing::warn!( "The {} health check is down: {:?}", name, err );
+                    Some( "down" )
+                }
+                Err( _ ) =>
+                {
+                    tracing::warn!( "The {} health check timed out after {:?}", name, CHECK_TIMEOUT );
+                    Some( "timed out" )
+                }
+            };
+
+            CheckReport {
+                name: name.clone(),
+                status: if reason.is_none() { HealthStatus::Up } else { HealthStatus::Down },
+                latency_ms,
+                reason,
+            }
+        } ) )
+        .await;
+
+        let status = if checks.iter().all( |check| check.status == HealthStatus::Up )
+        {
+            HealthStatus::Up
+        }
+        else
+        {
+            HealthStatus::Down
+        };
+
+        Readiness { status, checks }
+    }
+}
+
+/// Checks that files can be created in a directory, by writing and then removing a probe file.
+///
+/// # Errors
+///
+/// If the probe file can't be written or removed.
+pub async fn check_dir_writable( dir: &Path ) -> Result<()>
+{
+    let probe = dir.join( format!( ".health-{}", Uuid::new_v4() ) );
+
+    tokio::fs::write( &probe, b"" )
+        .await
+        .wrap_err_with( || format!( "{} is not writable", dir.display() ) )?;
+    tokio::fs::remove_file( &probe )
+        .await
+        .wrap_err_with( || format!( "Unable to remove {}", probe.display() ) )?;
+
+    Ok( () )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde( rename_all = "lowercase" )]
+pub enum HealthStatus
+{
+    Up,
+    Down,
+}
+
+/// Result of a readiness check.
+#[derive(Debug, Serialize)]
+pub struct CheckReport
+{
+    pub name:       String,
+    pub status:     HealthStatus,
+    pub latency_ms: f64,
+    /// Why the check is down, without the error details that are only logged.
+    #[serde( skip_serializing_if = "Option::is_none" )]
+    pub reason:     Option<&'static str>,
+}
+
+/// Result of the readiness checks, up if all of them are.
+#[derive(Debug, Serialize)]
+pub struct Readiness
+{
+    pub status: HealthStatus,
+    pub checks: Vec<CheckReport>,
+}
+
+#[derive(Serialize)]
+struct Liveness
+{
+    status: HealthStatus,
+}
+
+/// Router of the `/health/live` and `/health/ready` routes.
+pub fn router( checks: HealthChecks ) -> Router
+{
+    Router::new()
+        .route( "/health/live", get( live ) )
+        .route( "/health/ready", get( ready ) )
+        .with_state( Arc::new( checks ) )
+}
+
+async fn live() -> impl IntoResponse { Json( Liveness { status: HealthStatus::Up } ) }
+
+async fn ready( State( checks ): State<Arc<HealthChecks>> ) -> impl IntoResponse
+{
+    let readiness = checks.run().await;
+    let status_code = match readiness.status
+    {
+        HealthStatus::Up => StatusCode::OK,
+        HealthStatus::Down => StatusCode::SERVICE_UNAVAILABLE,
+    };
+
+    ( status_code, Json( readiness ) )
+}
+
+#[cfg( test )]
+#[allow( non_snake_case )]
+mod tests
+{
+    use super::*;
+
+    #[tokio::test]
+    async fn check_dir_writable__existing_and_missing_dirs__up_only_if_writable()
+    {
+        let dir = std::env::temp_dir().join( format!( "backend-health-{}", Uuid::new_v4() ) );
+        std::fs::create_dir_all( &dir ).unwrap();
+
+        let existing = check_dir_writable( &dir ).await;
+        let probes_left = std::fs::read_dir( &dir ).unwrap().count();
+        let missing = check_dir_writable( &dir.join( "missing" ) ).await;
+        std::fs::remove_dir_all( &dir ).unwrap();
+
+        assert!( existing.is_ok() );
+        assert_eq!( probes_left, 0 );
+        assert!( missing.unwrap_err().to_string().ends_with( "missing is not writable" ) );
+    }
+}
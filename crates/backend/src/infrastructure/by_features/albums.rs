@@ -0,0 +1,209 @@
+use crate::{services::features::domain::by_features::albums::Album, Result};
+
+use async_trait::async_trait;
+use sqlx::{sqlite::SqliteRow, Row, SqlitePool};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// Storage of the albums.
+#[async_trait]
+pub trait AlbumRepository: Send + Sync
+{
+    /// Lists all the albums ordered by position.
+    async fn list( &self ) -> Result<Vec<Album>>;
+
+    async fn get( &self, id: Uuid ) -> Result<Option<Album>>;
+
+    /// Position after the last album.
+    async fn next_position( &self ) -> Result<u32>;
+
+    async fn insert( &self, album: &Album ) -> Result<()>;
+
+    /// Updates an album, returns `false` if it doesn't exist.
+    async fn update( &self, album: &Album ) -> Result<bool>;
+
+    /// Deletes an album, returns `false` if it doesn't exist.
+    async fn delete( &self, id: Uuid ) -> Result<bool>;
+
+    /// Unsets the cover of the albums using the photo.
+    async fn clear_cover( &self, photo_id: Uuid ) -> Result<()>;
+}
+
+#[derive(Default)]
+pub struct InMemoryAlbumRepository
+{
+    albums: RwLock<HashMap<Uuid, Album>>,
+}
+
+#[async_trait]
+impl AlbumRepository for InMemoryAlbumRepository
+{
+    async fn list( &self ) -> Result<Vec<Album>>
+    {
+        let mut albums: Vec<Album> = self.albums.read().await.values().cloned().collect();
+        albums.sort_by_key( |album| ( album.position, album.created_at ) );
+
+        Ok( albums )
+    }
+
+    async fn get( &self, id: Uuid ) -> Result<Option<Album>> { Ok( self.albums.read().await.get( &id ).cloned() ) }
+
+    async fn next_position( &self ) -> Result<u32>
+    {
+        Ok( self
+            .albums
+            .read()
+            .await
+            .values()
+            .map( |album| album.position + 1 )
+            .max()
+            .unwrap_or( 0 ) )
+    }
+
+    async fn insert( &self, album: &Album ) -> Result<()>
+    {
+        self.albums.write().await.insert( album.id, album.clone() );
+        Ok( () )
+    }
+
+    async fn update( &self, album: &Album ) -> Result<bool>
+    {
+        let mut albums = self.albums.write().await;
+
+        Ok( albums.get_mut( &album.id ).map( |stored| *stored = album.clone() ).is_some() )
+    }
+
+    async fn delete( &self, id: Uuid ) -> Result<bool> { Ok( self.albums.write().await.remove( &id ).is_some() ) }
+
+    async fn clear_cover( &self, photo_id: Uuid ) -> Result<()>
+    {
+        self.albums
+            .write()
+            .await
+            .values_mut()
+            .filter( |album| album.cover_photo_id == Some( photo_id ) )
+            .for_each( |album| album.cover_photo_id = None );
+
+        Ok( () )
+    }
+}
+
+/// Albums stored on a sqlite database.
+pub struct SqliteAlbumRepository
+{
+    pool: SqlitePool,
+}
+
+impl SqliteAlbumRepository
+{
+    #[must_use]
+    pub const fn new( pool: SqlitePool ) -> Self { Self { pool } }
+
+    fn album_from_row( row: &SqliteRow ) -> Result<Album>
+    {
+        Ok( Album {
+            id:             Uuid::parse_str( row.try_get( "id" )? )?,
+            title:          row.try_get( "title" )?,
+            description:    row.try_get( "description" )?,
+            position:       row.try_get( "position" )?,
+            cover_photo_id: row
+                .try_get::<Option<&str>, _>( "cover_photo_id" )?
+                .map( Uuid::parse_str )
+                .transpose()?,
+            created_at:     row.try_get( "created_at" )?,
+            updated_at:     row.try_get( "updated_at" )?,
+        } )
+    }
+}
+
+#[async_trait]
+impl AlbumRepository for SqliteAlbumRepository
+{
+    async fn list( &self ) -> Result<Vec<Album>>
+    {
+        sqlx::query( "SELECT * FROM albums ORDER BY position, created_at" )
+            .fetch_all( &self.pool )
+            .await?
+            .iter()
+            .map( Self::album_from_row )
+            .collect()
+    }
+
+    async fn get( &self, id: Uuid ) -> Result<Option<Album>>
+    {
+        sqlx::query( "SELECT * FROM albums WHERE id = ?" )
+            .bind( id.to_string() )
+            .fetch_optional( &self.pool )
+            .await?
+            .as_ref()
+            .map( Self::album_from_row )
+            .transpose()
+    }
+
+    async fn next_position( &self ) -> Result<u32>
+    {
+        let position = sqlx::query_scalar( "SELECT COALESCE( MAX( position ) + 1, 0 ) FROM albums" )
+            .fetch_one( &self.pool )
+            .await?;
+
+        Ok( position )
+    }
+
+    async fn insert( &self, album: &Album ) -> Result<()>
+    {
+        sqlx::query(
+            "INSERT INTO albums ( id, title, description, position, cover_photo_id, created_at, updated_at ) \
+             VALUES ( ?, ?, ?, ?, ?, ?, ? )",
+        )
+        .bind( album.id.to_string() )
+        .bind( &album.title )
+        .bind( &album.description )
+        .bind( album.position )
+        .bind( album.cover_photo_id.map( |id| id.to_string() ) )
+        .bind( album.created_at )
+        .bind( album.updated_at )
+        .execute( &self.pool )
+        .await?;
+
+        Ok( () )
+    }
+
+    async fn update( &self, album: &Album ) -> Result<bool>
+    {
+        let result = sqlx::query(
+            "UPDATE albums SET title = ?, description = ?, position = ?, cover_photo_id = ?, updated_at = ? \
+             WHERE id = ?",
+        )
+        .bind( &album.title )
+        .bind( &album.description )
+        .bind( album.position )
+        .bind( album.cover_photo_id.map( |id| id.to_string() ) )
+        .bind( album.updated_at )
+        .bind( album.id.to_string() )
+        .execute( &self.pool )
+        .await?;
+
+        Ok( result.rows_affected() > 0 )
+    }
+
+    async fn delete( &self, id: Uuid ) -> Result<bool>
+    {
+        let result = sqlx::query( "DELETE FROM albums WHERE id = ?" )
+            .bind( id.to_string() )
+            .execute( &self.pool )
+            .await?;
+
+        Ok( result.rows_affected() > 0 )
+    }
+
+    async fn clear_cover( &self, photo_id: Uuid ) -> Result<()>
+    {
+        sqlx::query( "UPDATE albums SET cover_photo_id = NULL WHERE cover_photo_id = ?" )
+            .bind( photo_id.to_string() )
+            .execute( &self.pool )
+            .await?;
+
+        Ok( () )
+    }
+}
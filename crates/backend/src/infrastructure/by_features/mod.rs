@@ -1 +1,204 @@
+// Modules.
+pub mod albums;
+pub mod photos;
 
+use albums::{AlbumRepository, InMemoryAlbumRepository, SqliteAlbumRepository};
+use photos::{InMemoryPhotoRepository, PhotoRepository, SqlitePhotoRepository};
+
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+/// Repositories shared by the api routes.
+#[derive(Clone)]
+pub struct Repositories
+{
+    pub albums: Arc<dyn AlbumRepository>,
+    pub photos: Arc<dyn PhotoRepository>,
+}
+
+impl Repositories
+{
+    /// Repositories that keep the data in memory, lost when the server stops.
+    #[must_use]
+    pub fn in_memory() -> Self
+    {
+        Self {
+            albums: Arc::new( InMemoryAlbumRepository::default() ),
+            photos: Arc::new( InMemoryPhotoRepository::default() ),
+        }
+    }
+
+    /// Repositories that store the data on a sqlite database.
+    #[must_use]
+    pub fn sqlite( pool: &SqlitePool ) -> Self
+    {
+        Self {
+            albums: Arc::new( SqliteAlbumRepository::new( pool.clone() ) ),
+            photos: Arc::new( SqlitePhotoRepository::new( pool.clone() ) ),
+        }
+    }
+}
+
+#[cfg( test )]
+#[allow( non_snake_case )]
+mod tests
+{
+    use super::*;
+    use crate::services::features::{
+        domain::by_features::{
+            albums::{Album, AlbumFields},
+            photos::{Photo, PhotoDerivative, PhotoFields, PhotoFile, PhotoMetadata},
+        },
+        infrastructure::database,
+    };
+
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    fn album( title: &str, position: u32 ) -> Album
+    {
+        Album::new( AlbumFields {
+            title: title.to_owned(),
+            description: None,
+            position,
+            cover_photo_id: None,
+        } )
+    }
+
+    fn photo( album_id: Option<Uuid>, position: u32 ) -> Photo
+    {
+        Photo::new( PhotoFields {
+            album_id,
+            title: "Sunset".to_owned(),
+            description: Some( "Over the sea".to_owned() ),
+            position,
+            taken_at: None,
+        } )
+    }
+
+    /// Scenario run on every implementation of the album repository.
+    async fn albums_scenario( repositories: &Repositories )
+    {
+        let albums = &repositories.albums;
+        assert_eq!( albums.next_position().await.unwrap(), 0 );
+
+        let mut second = album( "Second", 1 );
+        let first = album( "First", 0 );
+        albums.insert( &second ).await.unwrap();
+        albums.insert( &first ).await.unwrap();
+
+        assert_eq!( albums.list().await.unwrap(), vec![first.clone(), second.clone()] );
+        assert_eq!( albums.next_position().await.unwrap(), 2 );
+        assert_eq!( albums.get( first.id ).await.unwrap(), Some( first.clone() ) );
+        assert_eq!( albums.get( Uuid::new_v4() ).await.unwrap(), None );
+
+        let cover_photo = photo( Some( second.id ), 0 );
+        let cover_photo_id = cover_photo.id;
+        repositories.photos.insert( &cover_photo ).await.unwrap();
+
+        second.update( AlbumFields {
+            title:          "Renamed".to_owned(),
+            description:    Some( "Now with a cover".to_owned() ),
+            position:       1,
+            cover_photo_id: Some( cover_photo_id ),
+        } );
+        assert!( albums.update( &second ).await.unwrap() );
+        assert_eq!( albums.get( second.id ).await.unwrap(), Some( second.clone() ) );
+        assert!( !albums.update( &album( "Missing", 3 ) ).await.unwrap() );
+
+        albums.clear_cover( cover_photo_id ).await.unwrap();
+        assert_eq!( albums.get( second.id ).await.unwrap().unwrap().cover_photo_id, None );
+
+        assert!( albums.delete( first.id ).await.unwrap() );
+        assert!( !albums.delete( first.id ).await.unwrap() );
+        assert_eq!( albums.list().await.unwrap().len(), 1 );
+    }
+
+    /// Scenario run on every implementation of the photo repository.
+    async fn photos_scenario( repositories: &Repositories )
+    {
+        let photos = &repositories.photos;
+        let trip = album( "Trip", 0 );
+        repositories.albums.insert( &trip ).await.unwrap();
+
+        let second = photo( Some( trip.id ), 1 );
+        let first = photo( Some( trip.id ), 0 );
+        let mut loose = photo( None, 0 );
+        loose.file = Some( PhotoFile {
+            file_name:     "original.jpg".to_owned(),
+            mime_type:     "image/jpeg".to_owned(),
+            size_bytes:    1024,
+            original_name: Some( "sunset.jpg".to_owned() ),
+            derivatives:   Vec::new(),
+            metadata:      PhotoMetadata {
+                camera_model: Some( "X100V".to_owned() ),
+                orientation: Some( 6 ),
+                ..PhotoMetadata::default()
+            },
+        } );
+
+        for photo in [&second, &first, &loose]
+        {
+            photos.insert( photo ).await.unwrap();
+        }
+
+        assert_eq!( photos.list( Some( trip.id ) ).await.unwrap(), vec![first.clone(), second.clone()] );
+        assert_eq!( photos.list( None ).await.unwrap().len(), 3 );
+        assert_eq!( photos.next_position( Some( trip.id ) ).await.unwrap(), 2 );
+        assert_eq!( photos.next_position( None ).await.unwrap(), 1 );
+        assert_eq!( photos.get( loose.id ).await.unwrap(), Some( loose.clone() ) );
+
+        let derivatives = vec![PhotoDerivative {
+            name:       "thumbnail".to_owned(),
+            file_name:  "thumbnail.webp".to_owned(),
+            mime_type:  "image/webp".to_owned(),
+            width:      320,
+            height:     213,
+            size_bytes: 512,
+        }];
+        assert!( photos.set_derivatives( loose.id, &derivatives ).await.unwrap() );
+        assert!( !photos.set_derivatives( first.id, &derivatives ).await.unwrap() );
+        assert_eq!( photos.get( loose.id ).await.unwrap().unwrap().file.unwrap().derivatives, derivatives );
+
+        photos.detach_from_album( trip.id ).await.unwrap();
+        assert!( photos.list( Some( trip.id ) ).await.unwrap().is_empty() );
+        assert_eq!( photos.get( first.id ).await.unwrap().unwrap().album_id, None );
+
+        assert!( photos.delete( first.id ).await.unwrap() );
+        assert!( !photos.delete( first.id ).await.unwrap() );
+        assert!( !photos.update( &first ).await.unwrap() );
+    }
+
+    #[tokio::test]
+    async fn in_memory__albums_crud__ordered_by_position()
+    {
+        albums_scenario( &Repositories::in_memory() ).await;
+    }
+
+    #[tokio::test]
+    async fn in_memory__photos_crud__ordered_by_position_per_album()
+    {
+        photos_scenario( &Repositories::in_memory() ).await;
+    }
+
+    /// Repositories on a new in memory database, with a single connection as each one opens its own database.
+    async fn sqlite_repositories() -> Repositories
+    {
+        let pool = database::connect( "sqlite::memory:", 1, Duration::from_secs( 5 ) ).await.unwrap();
+        database::migrate( &pool ).await.unwrap();
+
+        Repositories::sqlite( &pool )
+    }
+
+    #[tokio::test]
+    async fn sqlite__albums_crud__ordered_by_position()
+    {
+        albums_scenario( &sqlite_repositories().await ).await;
+    }
+
+    #[tokio::test]
+    async fn sqlite__photos_crud__ordered_by_position_per_album()
+    {
+        photos_scenario( &sqlite_repositories().await ).await;
+    }
+}
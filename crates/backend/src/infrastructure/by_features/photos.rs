@@ -0,0 +1,432 @@
+use crate::{
+    services::features::domain::by_features::photos::{GpsPosition, Photo, PhotoDerivative, PhotoFile, PhotoMetadata},
+    Result,
+};
+
+use async_trait::async_trait;
+use sqlx::{sqlite::SqliteRow, Row, Sqlite, SqlitePool, Transaction};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// Storage of the photos.
+#[async_trait]
+pub trait PhotoRepository: Send + Sync
+{
+    /// Lists the photos ordered by position, only those of `album_id` if set.
+    async fn list( &self, album_id: Option<Uuid> ) -> Result<Vec<Photo>>;
+
+    async fn get( &self, id: Uuid ) -> Result<Option<Photo>>;
+
+    /// Position after the last photo of the album.
+    async fn next_position( &self, album_id: Option<Uuid> ) -> Result<u32>;
+
+    async fn insert( &self, photo: &Photo ) -> Result<()>;
+
+    /// Updates a photo, returns `false` if it doesn't exist.
+    async fn update( &self, photo: &Photo ) -> Result<bool>;
+
+    /// Deletes a photo, returns `false` if it doesn't exist.
+    async fn delete( &self, id: Uuid ) -> Result<bool>;
+
+    /// Removes all the photos from an album, keeping them as photos without album.
+    async fn detach_from_album( &self, album_id: Uuid ) -> Result<()>;
+
+    /// Replaces the derivatives of the file of a photo, returns `false` if the photo has no file.
+    async fn set_derivatives( &self, id: Uuid, derivatives: &[PhotoDerivative] ) -> Result<bool>;
+}
+
+#[derive(Default)]
+pub struct InMemoryPhotoRepository
+{
+    photos: RwLock<HashMap<Uuid, Photo>>,
+}
+
+#[async_trait]
+impl PhotoRepository for InMemoryPhotoRepository
+{
+    async fn list( &self, album_id: Option<Uuid> ) -> Result<Vec<Photo>>
+    {
+        let mut photos: Vec<Photo> = self
+            .photos
+            .read()
+            .await
+            .values()
+            .filter( |photo| album_id.is_none() || photo.album_id == album_id )
+            .cloned()
+            .collect();
+        photos.sort_by_key( |photo| ( photo.position, photo.created_at ) );
+
+        Ok( photos )
+    }
+
+    async fn get( &self, id: Uuid ) -> Result<Option<Photo>> { Ok( self.photos.read().await.get( &id ).cloned() ) }
+
+    async fn next_position( &self, album_id: Option<Uuid> ) -> Result<u32>
+    {
+        Ok( self
+            .photos
+            .read()
+            .await
+            .values()
+            .filter( |photo| photo.album_id == album_id )
+            .map( |photo| photo.position + 1 )
+            .max()
+            .unwrap_or( 0 ) )
+    }
+
+    async fn insert( &self, photo: &Photo ) -> Result<()>
+    {
+        self.photos.write().await.insert( photo.id, photo.clone() );
+        Ok( () )
+    }
+
+    async fn update( &self, photo: &Photo ) -> Result<bool>
+    {
+        let mut photos = self.photos.write().await;
+
+        Ok( photos.get_mut( &photo.id ).map( |stored| *stored = photo.clone() ).is_some() )
+    }
+
+    async fn delete( &self, id: Uuid ) -> Result<bool> { Ok( self.photos.write().await.remove( &id ).is_some() ) }
+
+    async fn detach_from_album( &self, album_id: Uuid ) -> Result<()>
+    {
+        self.photos
+            .write()
+            .await
+            .values_mut()
+            .filter( |photo| photo.album_id == Some( album_id ) )
+            .for_each( |photo| photo.album_id = None );
+
+        Ok( () )
+    }
+
+    async fn set_derivatives( &self, id: Uuid, derivatives: &[PhotoDerivative] ) -> Result<bool>
+    {
+        let mut photos = self.photos.write().await;
+
+        Ok( photos
+            .get_mut( &id )
+            .and_then( |photo| photo.file.as_mut() )
+            .map( |file| file.derivatives = derivatives.to_vec() )
+            .is_some() )
+    }
+}
+
+/// Photos stored on a sqlite database.
+pub struct SqlitePhotoRepository
+{
+    pool: SqlitePool,
+}
+
+impl SqlitePhotoRepository
+{
+    #[must_use]
+    pub const fn new( pool: SqlitePool ) -> Self { Self { pool } }
+
+    fn photo_from_row( row: &SqliteRow ) -> Result<Photo>
+    {
+        let file = match row.try_get::<Option<String>, _>( "file_name" )?
+        {
+            Some( file_name ) => Some( PhotoFile {
+                file_name,
+                mime_type:     row.try_get::<Option<String>, _>( "mime_type" )?.unwrap_or_default(),
+                size_bytes:    u64::try_from( row.try_get::<Option<i64>, _>( "size_bytes" )?.unwrap_or_default() )?,
+                original_name: row.try_get( "original_name" )?,
+                derivatives:   Vec::new(),
+                metadata:      Self::metadata_from_row( row )?,
+            } ),
+            None => None,
+        };
+
+        Ok( Photo {
+            id:          Uuid::parse_str( row.try_get( "id" )? )?,
+            album_id:    row
+                .try_get::<Option<&str>, _>( "album_id" )?
+                .map( Uuid::parse_str )
+                .transpose()?,
+            title:       row.try_get( "title" )?,
+            description: row.try_get( "description" )?,
+            position:    row.try_get( "position" )?,
+            taken_at:    row.try_get( "taken_at" )?,
+            file,
+            created_at:  row.try_get( "created_at" )?,
+            updated_at:  row.try_get( "updated_at" )?,
+        } )
+    }
+
+    fn metadata_from_row( row: &SqliteRow ) -> Result<PhotoMetadata>
+    {
+        let gps = match (
+            row.try_get::<Option<f64>, _>( "gps_latitude" )?,
+            row.try_get::<Option<f64>, _>( "gps_longitude" )?,
+        )
+        {
+            ( Some( latitude ), Some( longitude ) ) => Some( GpsPosition {
+                latitude,
+                longitude,
+                altitude_m: row.try_get( "gps_altitude_m" )?,
+            } ),
+            _ => None,
+        };
+
+        Ok( PhotoMetadata {
+            camera_make:        row.try_get( "camera_make" )?,
+            camera_model:       row.try_get( "camera_model" )?,
+            lens_model:         row.try_get( "lens_model" )?,
+            focal_length_mm:    row.try_get( "focal_length_mm" )?,
+            f_number:           row.try_get( "f_number" )?,
+            exposure_time_secs: row.try_get( "exposure_time_secs" )?,
+            iso:                row.try_get( "iso" )?,
+            taken_at:           row.try_get( "metadata_taken_at" )?,
+            gps,
+            orientation:        row.try_get( "orientation" )?,
+            rating:             row.try_get( "rating" )?,
+        } )
+    }
+
+    fn derivative_from_row( row: &SqliteRow ) -> Result<( Uuid, PhotoDerivative )>
+    {
+        Ok( (
+            Uuid::parse_str( row.try_get( "photo_id" )? )?,
+            PhotoDerivative {
+                name:       row.try_get( "name" )?,
+                file_name:  row.try_get( "file_name" )?,
+                mime_type:  row.try_get( "mime_type" )?,
+                width:      row.try_get( "width" )?,
+                height:     row.try_get( "height" )?,
+                size_bytes: u64::try_from( row.try_get::<i64, _>( "size_bytes" )? )?,
+            },
+        ) )
+    }
+
+    async fn insert_derivatives(
+        transaction: &mut Transaction<'_, Sqlite>,
+        id: Uuid,
+        derivatives: &[PhotoDerivative],
+    ) -> Result<()>
+    {
+        for derivative in derivatives
+        {
+            sqlx::query(
+                "INSERT INTO photo_derivatives ( photo_id, name, file_name, mime_type, width, height, size_bytes ) \
+                 VALUES ( ?, ?, ?, ?, ?, ?, ? )",
+            )
+            .bind( id.to_string() )
+            .bind( &derivative.name )
+            .bind( &derivative.file_name )
+            .bind( &derivative.mime_type )
+            .bind( derivative.width )
+            .bind( derivative.height )
+            .bind( i64::try_from( derivative.size_bytes )? )
+            .execute( &mut *transaction )
+            .await?;
+        }
+
+        Ok( () )
+    }
+
+    /// Sets the derivatives of the photos with a file, from the derivative rows.
+    fn attach_derivatives( photos: &mut [Photo], rows: &[SqliteRow] ) -> Result<()>
+    {
+        let mut derivatives: HashMap<Uuid, Vec<PhotoDerivative>> = HashMap::new();
+
+        for row in rows
+        {
+            let ( photo_id, derivative ) = Self::derivative_from_row( row )?;
+            derivatives.entry( photo_id ).or_default().push( derivative );
+        }
+
+        for photo in photos
+        {
+            if let Some( file ) = photo.file.as_mut()
+            {
+                file.derivatives = derivatives.remove( &photo.id ).unwrap_or_default();
+            }
+        }
+
+        Ok( () )
+    }
+}
+
+#[async_trait]
+impl PhotoRepository for SqlitePhotoRepository
+{
+    async fn list( &self, album_id: Option<Uuid> ) -> Result<Vec<Photo>>
+    {
+        let album_id = album_id.map( |id| id.to_string() );
+
+        let mut photos = sqlx::query(
+            "SELECT * FROM photos WHERE ?1 IS NULL OR album_id = ?1 ORDER BY position, created_at",
+        )
+        .bind( &album_id )
+        .fetch_all( &self.pool )
+        .await?
+        .iter()
+        .map( Self::photo_from_row )
+        .collect::<Result<Vec<_>>>()?;
+
+        let derivative_rows = sqlx::query(
+            "SELECT photo_derivatives.* FROM photo_derivatives JOIN photos ON photos.id = photo_derivatives.photo_id \
+             WHERE ?1 IS NULL OR photos.album_id = ?1 ORDER BY MAX( width, height )",
+        )
+        .bind( &album_id )
+        .fetch_all( &self.pool )
+        .await?;
+
+        Self::attach_derivatives( &mut photos, &derivative_rows )?;
+
+        Ok( photos )
+    }
+
+    async fn get( &self, id: Uuid ) -> Result<Option<Photo>>
+    {
+        let mut photo = match sqlx::query( "SELECT * FROM photos WHERE id = ?" )
+            .bind( id.to_string() )
+            .fetch_optional( &self.pool )
+            .await?
+        {
+            Some( row ) => Self::photo_from_row( &row )?,
+            None => return Ok( None ),
+        };
+
+        let derivative_rows =
+            sqlx::query( "SELECT * FROM photo_derivatives WHERE photo_id = ? ORDER BY MAX( width, height )" )
+                .bind( id.to_string() )
+                .fetch_all( &self.pool )
+                .await?;
+
+        Self::attach_derivatives( std::slice::from_mut( &mut photo ), &derivative_rows )?;
+
+        Ok( Some( photo ) )
+    }
+
+    async fn next_position( &self, album_id: Option<Uuid> ) -> Result<u32>
+    {
+        let position =
+            sqlx::query_scalar( "SELECT COALESCE( MAX( position ) + 1, 0 ) FROM photos WHERE album_id IS ?" )
+                .bind( album_id.map( |id| id.to_string() ) )
+                .fetch_one( &self.pool )
+                .await?;
+
+        Ok( position )
+    }
+
+    async fn insert( &self, photo: &Photo ) -> Result<()>
+    {
+        let file = photo.file.as_ref();
+        let metadata = file.map( |file| &file.metadata );
+        let gps = metadata.and_then( |metadata| metadata.gps );
+        let mut transaction = self.pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO photos ( id, album_id, title, description, position, taken_at, file_name, mime_type, \
+             size_bytes, original_name, camera_make, camera_model, lens_model, focal_length_mm, f_number, \
+             exposure_time_secs, iso, metadata_taken_at, gps_latitude, gps_longitude, gps_altitude_m, orientation, \
+             rating, created_at, updated_at ) \
+             VALUES ( ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ? )",
+        )
+        .bind( photo.id.to_string() )
+        .bind( photo.album_id.map( |id| id.to_string() ) )
+        .bind( &photo.title )
+        .bind( &photo.description )
+        .bind( photo.position )
+        .bind( photo.taken_at )
+        .bind( file.map( |file| &file.file_name ) )
+        .bind( file.map( |file| &file.mime_type ) )
+        .bind( file.map( |file| i64::try_from( file.size_bytes ) ).transpose()? )
+        .bind( file.and_then( |file| file.original_name.as_ref() ) )
+        .bind( metadata.and_then( |metadata| metadata.camera_make.as_ref() ) )
+        .bind( metadata.and_then( |metadata| metadata.camera_model.as_ref() ) )
+        .bind( metadata.and_then( |metadata| metadata.lens_model.as_ref() ) )
+        .bind( metadata.and_then( |metadata| metadata.focal_length_mm ) )
+        .bind( metadata.and_then( |metadata| metadata.f_number ) )
+        .bind( metadata.and_then( |metadata| metadata.exposure_time_secs ) )
+        .bind( metadata.and_then( |metadata| metadata.iso ) )
+        .bind( metadata.and_then( |metadata| metadata.taken_at ) )
+        .bind( gps.map( |gps| gps.latitude ) )
+        .bind( gps.map( |gps| gps.longitude ) )
+        .bind( gps.and_then( |gps| gps.altitude_m ) )
+        .bind( metadata.and_then( |metadata| metadata.orientation ) )
+        .bind( metadata.and_then( |metadata| metadata.rating ) )
+        .bind( photo.created_at )
+        .bind( photo.updated_at )
+        .execute( &mut transaction )
+        .await?;
+
+        if let Some( file ) = file
+        {
+            Self::insert_derivatives( &mut transaction, photo.id, &file.derivatives ).await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok( () )
+    }
+
+    async fn update( &self, photo: &Photo ) -> Result<bool>
+    {
+        let result = sqlx::query(
+            "UPDATE photos SET album_id = ?, title = ?, description = ?, position = ?, taken_at = ?, updated_at = ? \
+             WHERE id = ?",
+        )
+        .bind( photo.album_id.map( |id| id.to_string() ) )
+        .bind( &photo.title )
+        .bind( &photo.description )
+        .bind( photo.position )
+        .bind( photo.taken_at )
+        .bind( photo.updated_at )
+        .bind( photo.id.to_string() )
+        .execute( &self.pool )
+        .await?;
+
+        Ok( result.rows_affected() > 0 )
+    }
+
+    async fn delete( &self, id: Uuid ) -> Result<bool>
+    {
+        let result = sqlx::query( "DELETE FROM photos WHERE id = ?" )
+            .bind( id.to_string() )
+            .execute( &self.pool )
+            .await?;
+
+        Ok( result.rows_affected() > 0 )
+    }
+
+    async fn detach_from_album( &self, album_id: Uuid ) -> Result<()>
+    {
+        sqlx::query( "UPDATE photos SET album_id = NULL WHERE album_id = ?" )
+            .bind( album_id.to_string() )
+            .execute( &self.pool )
+            .await?;
+
+        Ok( () )
+    }
+
+    async fn set_derivatives( &self, id: Uuid, derivatives: &[PhotoDerivative] ) -> Result<bool>
+    {
+        let mut transaction = self.pool.begin().await?;
+
+        let has_file: bool =
+            sqlx::query_scalar( "SELECT EXISTS ( SELECT 1 FROM photos WHERE id = ? AND file_name IS NOT NULL )" )
+                .bind( id.to_string() )
+                .fetch_one( &mut transaction )
+                .await?;
+
+        if !has_file
+        {
+            return Ok( false );
+        }
+
+        sqlx::query( "DELETE FROM photo_derivatives WHERE photo_id = ?" )
+            .bind( id.to_string() )
+            .execute( &mut transaction )
+            .await?;
+
+        Self::insert_derivatives( &mut transaction, id, derivatives ).await?;
+        transaction.commit().await?;
+
+        Ok( true )
+    }
+}
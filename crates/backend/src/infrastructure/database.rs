@@ -0,0 +1,91 @@
+use crate::Result;
+
+use sqlx::{
+    migrate::Migrator,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    SqlitePool,
+};
+use std::{str::FromStr, time::Duration};
+
+/// Versioned migrations embedded in the binary from the `migrations` directory.
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+/// Creates a sqlite connection pool, creating the database file if missing.
+///
+/// # Arguments
+///
+/// * `url` - The database url ( e.g. `sqlite://./data/photo-story.db` ).
+/// * `max_connections` - The maximum number of connections of the pool.
+/// * `acquire_timeout` - The maximum time to wait for a connection.
+///
+/// # Errors
+///
+/// If the url is invalid or the database can't be opened.
+pub async fn connect( url: &str, max_connections: u32, acquire_timeout: Duration ) -> Result<SqlitePool>
+{
+    let connect_options = SqliteConnectOptions::from_str( url )?
+        .create_if_missing( true )
+        .foreign_keys( true );
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections( max_connections )
+        .acquire_timeout( acquire_timeout )
+        .connect_with( connect_options )
+        .await?;
+
+    Ok( pool )
+}
+
+/// Applies the embedded migrations not yet applied to the database.
+///
+/// # Errors
+///
+/// If a migration fails or was modified after being applied.
+pub async fn migrate( pool: &SqlitePool ) -> Result<()>
+{
+    MIGRATOR.run( pool ).await?;
+    tracing::info!( "Database migrations applied" );
+
+    Ok( () )
+}
+
+/// Checks that the database answers a query.
+///
+/// # Errors
+///
+/// If no connection can be acquired or the query fails.
+pub async fn ping( pool: &SqlitePool ) -> Result<()>
+{
+    sqlx::query( "SELECT 1" ).execute( pool ).await?;
+
+    Ok( () )
+}
+
+#[cfg( test )]
+#[allow( non_snake_case )]
+mod tests
+{
+    use super::*;
+
+    #[tokio::test]
+    async fn migrate__fresh_database__all_migrations_applied_once()
+    {
+        let pool = connect( "sqlite::memory:", 1, Duration::from_secs( 5 ) ).await.unwrap();
+
+        migrate( &pool ).await.unwrap();
+        migrate( &pool ).await.unwrap();
+
+        let applied: i64 = sqlx::query_scalar( "SELECT COUNT(*) FROM _sqlx_migrations WHERE success" )
+            .fetch_one( &pool )
+            .await
+            .unwrap();
+        assert_eq!( usize::try_from( applied ).unwrap(), MIGRATOR.iter().count() );
+
+        for table in ["albums", "photos", "photo_derivatives"]
+        {
+            sqlx::query( &format!( "SELECT * FROM {table}" ) ).fetch_all( &pool ).await.unwrap();
+        }
+
+        ping( &pool ).await.unwrap();
+    }
+}
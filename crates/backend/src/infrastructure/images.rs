@@ -0,0 +1,295 @@
+use super::photo_metadata;
+use crate::{services::features::domain::by_features::photos::PhotoDerivative, Result};
+
+use image::{
+    codecs::{jpeg::JpegEncoder, webp::WebPEncoder},
+    imageops::FilterType,
+    ColorType, DynamicImage, GenericImageView,
+};
+use rgb::FromSlice;
+use serde::Deserialize;
+use std::path::Path;
+
+/// Encoding speed of the AVIF derivatives, from 1 (slowest, smallest) to 10 (fastest).
+const AVIF_SPEED: u8 = 8;
+
+/// Image format of the photo derivatives.
+///
+/// All of them are encoded in pure Rust. WebP derivatives are lossless, as there is no pure Rust
+/// lossy WebP encoder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde( rename_all = "lowercase" )]
+pub enum DerivativeFormat
+{
+    Avif,
+    Webp,
+    Jpeg,
+}
+
+impl DerivativeFormat
+{
+    #[must_use]
+    pub const fn extension( self ) -> &'static str
+    {
+        match self
+        {
+            Self::Avif => "avif",
+            Self::Webp => "webp",
+            Self::Jpeg => "jpg",
+        }
+    }
+
+    #[must_use]
+    pub const fn mime_type( self ) -> &'static str
+    {
+        match self
+        {
+            Self::Avif => "image/avif",
+            Self::Webp => "image/webp",
+            Self::Jpeg => "image/jpeg",
+        }
+    }
+
+    /// Encodes an image in this format.
+    fn encode( self, image: &DynamicImage, quality: u8 ) -> Result<Vec<u8>>
+    {
+        let ( width, height ) = image.dimensions();
+        let mut bytes = Vec::new();
+
+        match self
+        {
+            Self::Avif =>
+            {
+                let rgba = image.to_rgba8();
+                let avif = ravif::Encoder::new()
+                    .with_quality( f32::from( quality ) )
+                    .with_speed( AVIF_SPEED )
+                    .encode_rgba( ravif::Img::new( rgba.as_raw().as_rgba(), width as usize, height as usize ) )?;
+
+                bytes = avif.avif_file;
+            }
+            Self::Webp if image.color().has_alpha() =>
+            {
+                WebPEncoder::new_lossless( &mut bytes ).encode(
+                    image.to_rgba8().as_raw(),
+                    width,
+                    height,
+                    ColorType::Rgba8,
+                )?;
+            }
+            Self::Webp =>
+            {
+                WebPEncoder::new_lossless( &mut bytes ).encode(
+                    image.to_rgb8().as_raw(),
+                    width,
+                    height,
+                    ColorType::Rgb8,
+                )?;
+            }
+            Self::Jpeg =>
+            {
+                JpegEncoder::new_with_quality( &mut bytes, quality ).encode(
+                    image.to_rgb8().as_raw(),
+                    width,
+                    height,
+                    ColorType::Rgb8,
+                )?;
+            }
+        }
+
+        Ok( bytes )
+    }
+}
+
+/// Size of the photo derivatives.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DerivativeSize
+{
+    /// Name of the size, used as the file name of its derivatives, e.g. `thumbnail.webp`.
+    pub name:          String,
+    /// Maximum width and height, smaller originals are never upscaled.
+    pub max_dimension: u32,
+}
+
+/// Sizes and formats of the derivatives generated for each photo.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DerivativeOptions
+{
+    pub sizes:   Vec<DerivativeSize>,
+    pub formats: Vec<DerivativeFormat>,
+    /// Quality of the lossy formats, from 1 to 100.
+    pub quality: u8,
+}
+
+/// Generates the derivatives of an original image on a directory, one file per size and format.
+///
+/// The EXIF orientation of the original is applied, so the derivatives are always upright. This is
+/// CPU bound and blocking, run it outside of the async runtime.
+///
+/// # Errors
+///
+/// If the original can't be decoded, e.g. AVIF originals, or a derivative can't be encoded or written.
+pub fn generate_derivatives( original: &Path, dir: &Path, options: &DerivativeOptions ) -> Result<Vec<PhotoDerivative>>
+{
+    let image = image::io::Reader::open( original )?.with_guessed_format()?.decode()?;
+    let image = apply_orientation( image, photo_metadata::read_orientation( original ) );
+    let mut derivatives = Vec::with_capacity( options.sizes.len() * options.formats.len() );
+
+    for size in &options.sizes
+    {
+        let resized = if image.width().max( image.height() ) > size.max_dimension
+        {
+            image.resize( size.max_dimension, size.max_dimension, FilterType::Lanczos3 )
+        }
+        else
+        {
+            image.clone()
+        };
+
+        for format in &options.formats
+        {
+            let bytes = format.encode( &resized, options.quality )?;
+            let file_name = format!( "{}.{}", size.name, format.extension() );
+            std::fs::write( dir.join( &file_name ), &bytes )?;
+
+            derivatives.push( PhotoDerivative {
+                name:       size.name.clone(),
+                file_name,
+                mime_type:  format.mime_type().to_owned(),
+                width:      resized.width(),
+                height:     resized.height(),
+                size_bytes: bytes.len() as u64,
+            } );
+        }
+    }
+
+    derivatives.sort_by_key( |derivative| derivative.width.max( derivative.height ) );
+
+    Ok( derivatives )
+}
+
+/// Rotates and flips an image so that it is upright, according to its EXIF orientation.
+fn apply_orientation( image: DynamicImage, orientation: Option<u16> ) -> DynamicImage
+{
+    match orientation
+    {
+        Some( 2 ) => image.fliph(),
+        Some( 3 ) => image.rotate180(),
+        Some( 4 ) => image.flipv(),
+        Some( 5 ) => image.rotate90().fliph(),
+        Some( 6 ) => image.rotate90(),
+        Some( 7 ) => image.rotate270().fliph(),
+        Some( 8 ) => image.rotate270(),
+        _ => image,
+    }
+}
+
+#[cfg( test )]
+#[allow( non_snake_case )]
+mod tests
+{
+    use super::*;
+
+    use image::{ImageFormat, Rgb, RgbImage, Rgba, RgbaImage};
+
+    const MARKER: Rgb<u8> = Rgb( [255, 0, 0] );
+
+    /// Image of 3 x 2 pixels with a marker on its top left pixel.
+    fn marked_image() -> DynamicImage
+    {
+        let mut image = RgbImage::new( 3, 2 );
+        image.put_pixel( 0, 0, MARKER );
+
+        DynamicImage::ImageRgb8( image )
+    }
+
+    fn marker_position( image: &DynamicImage ) -> ( u32, u32 )
+    {
+        let image = image.to_rgb8();
+        let ( x, y, _ ) = image.enumerate_pixels().find( |( _, _, pixel )| **pixel == MARKER ).unwrap();
+
+        ( x, y )
+    }
+
+    #[test]
+    fn apply_orientation__exif_orientations__upright()
+    {
+        let cases = [
+            ( None, ( 3, 2 ), ( 0, 0 ) ),
+            ( Some( 1 ), ( 3, 2 ), ( 0, 0 ) ),
+            ( Some( 2 ), ( 3, 2 ), ( 2, 0 ) ),
+            ( Some( 3 ), ( 3, 2 ), ( 2, 1 ) ),
+            ( Some( 4 ), ( 3, 2 ), ( 0, 1 ) ),
+            ( Some( 5 ), ( 2, 3 ), ( 0, 0 ) ),
+            ( Some( 6 ), ( 2, 3 ), ( 1, 0 ) ),
+            ( Some( 7 ), ( 2, 3 ), ( 1, 2 ) ),
+            ( Some( 8 ), ( 2, 3 ), ( 0, 2 ) ),
+            ( Some( 9 ), ( 3, 2 ), ( 0, 0 ) ),
+        ];
+
+        for ( orientation, dimensions, position ) in cases
+        {
+            let image = apply_orientation( marked_image(), orientation );
+
+            assert_eq!( image.dimensions(), dimensions, "orientation {orientation:?}" );
+            assert_eq!( marker_position( &image ), position, "orientation {orientation:?}" );
+        }
+    }
+
+    #[test]
+    fn encode__each_format__encoded_in_the_format()
+    {
+        let rgb = DynamicImage::ImageRgb8( RgbImage::from_pixel( 4, 4, Rgb( [10, 20, 30] ) ) );
+        let rgba = DynamicImage::ImageRgba8( RgbaImage::from_pixel( 4, 4, Rgba( [10, 20, 30, 128] ) ) );
+
+        for image in [&rgb, &rgba]
+        {
+            let jpeg = DerivativeFormat::Jpeg.encode( image, 80 ).unwrap();
+            let webp = DerivativeFormat::Webp.encode( image, 80 ).unwrap();
+            let avif = DerivativeFormat::Avif.encode( image, 80 ).unwrap();
+
+            assert_eq!( image::guess_format( &jpeg ).unwrap(), ImageFormat::Jpeg );
+            assert_eq!( image::guess_format( &webp ).unwrap(), ImageFormat::WebP );
+            assert_eq!( &avif[4..12], b"ftypavif" );
+            assert_eq!( image::load_from_memory( &jpeg ).unwrap().dimensions(), ( 4, 4 ) );
+            assert_eq!( image::load_from_memory( &webp ).unwrap().dimensions(), ( 4, 4 ) );
+        }
+    }
+
+    #[test]
+    fn generate_derivatives__sizes_and_formats__downscaled_but_never_upscaled()
+    {
+        let dir = std::env::temp_dir().join( format!( "backend-images-{}", uuid::Uuid::new_v4() ) );
+        std::fs::create_dir_all( &dir ).unwrap();
+        let original = dir.join( "original.png" );
+        RgbImage::new( 40, 20 ).save( &original ).unwrap();
+
+        let options = DerivativeOptions {
+            sizes:   vec![
+                DerivativeSize { name: "large".to_owned(), max_dimension: 100 },
+                DerivativeSize { name: "thumbnail".to_owned(), max_dimension: 10 },
+            ],
+            formats: vec![DerivativeFormat::Webp, DerivativeFormat::Jpeg],
+            quality: 80,
+        };
+        let derivatives = generate_derivatives( &original, &dir, &options ).unwrap();
+        let written = derivatives
+            .iter()
+            .all( |derivative| dir.join( &derivative.file_name ).metadata().unwrap().len() == derivative.size_bytes );
+        std::fs::remove_dir_all( &dir ).unwrap();
+
+        let summary = derivatives
+            .iter()
+            .map( |derivative| {
+                ( derivative.file_name.as_str(), derivative.mime_type.as_str(), derivative.width, derivative.height )
+            } )
+            .collect::<Vec<_>>();
+        assert_eq!( summary, vec![
+            ( "thumbnail.webp", "image/webp", 10, 5 ),
+            ( "thumbnail.jpg", "image/jpeg", 10, 5 ),
+            ( "large.webp", "image/webp", 40, 20 ),
+            ( "large.jpg", "image/jpeg", 40, 20 ),
+        ] );
+        assert!( written );
+    }
+}
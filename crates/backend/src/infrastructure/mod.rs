@@ -1,2 +1,6 @@
 // Modules.
 pub mod by_features;
+pub mod database;
+pub mod images;
+pub mod photo_metadata;
+pub mod storage;
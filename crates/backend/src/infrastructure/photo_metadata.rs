@@ -0,0 +1,233 @@
+use crate::services::features::domain::by_features::photos::{GpsPosition, PhotoMetadata};
+
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone, Utc};
+use exif::{Exif, In, Tag, Value};
+use std::{
+    fs::File,
+    io::{BufReader, Read},
+    path::Path,
+};
+
+/// Number of bytes at the start of a file searched for the XMP packet.
+const XMP_SCAN_BYTES: u64 = 512 * 1024;
+
+/// Reads the camera metadata of an image file from its EXIF and XMP data.
+///
+/// This is blocking, run it outside of the async runtime. Missing or malformed data is left unset.
+#[must_use]
+pub fn extract_metadata( path: &Path ) -> PhotoMetadata
+{
+    let mut metadata = read_exif( path ).map( |exif| metadata_from_exif( &exif ) ).unwrap_or_default();
+
+    if let Some( xmp ) = read_xmp_packet( path )
+    {
+        metadata.lens_model = metadata
+            .lens_model
+            .or_else( || xmp_property( &xmp, "exifEX:LensModel" ) )
+            .or_else( || xmp_property( &xmp, "aux:Lens" ) );
+        metadata.rating = xmp_property( &xmp, "xmp:Rating" ).and_then( |rating| rating.parse().ok() );
+    }
+
+    metadata
+}
+
+/// Reads the EXIF orientation of an image file, from 1 to 8.
+#[must_use]
+pub fn read_orientation( path: &Path ) -> Option<u16>
+{
+    read_exif( path ).and_then( |exif| orientation( &exif ) )
+}
+
+fn read_exif( path: &Path ) -> Option<Exif>
+{
+    let file = File::open( path ).ok()?;
+
+    exif::Reader::new().read_from_container( &mut BufReader::new( file ) ).ok()
+}
+
+fn metadata_from_exif( exif: &Exif ) -> PhotoMetadata
+{
+    PhotoMetadata {
+        camera_make:        ascii( exif, Tag::Make ),
+        camera_model:       ascii( exif, Tag::Model ),
+        lens_model:         ascii( exif, Tag::LensModel ),
+        focal_length_mm:    rational( exif, Tag::FocalLength, 0 ),
+        f_number:           rational( exif, Tag::FNumber, 0 ),
+        exposure_time_secs: rational( exif, Tag::ExposureTime, 0 ),
+        iso:                exif
+            .get_field( Tag::PhotographicSensitivity, In::PRIMARY )
+            .and_then( |field| field.value.get_uint( 0 ) ),
+        taken_at:           taken_at( exif ),
+        gps:                gps_position( exif ),
+        orientation:        orientation( exif ),
+        rating:             None,
+    }
+}
+
+fn orientation( exif: &Exif ) -> Option<u16>
+{
+    exif.get_field( Tag::Orientation, In::PRIMARY )
+        .and_then( |field| field.value.get_uint( 0 ) )
+        .and_then( |orientation| u16::try_from( orientation ).ok() )
+        .filter( |orientation| ( 1..=8 ).contains( orientation ) )
+}
+
+/// First string of an ASCII field, trimmed, `None` if blank.
+fn ascii( exif: &Exif, tag: Tag ) -> Option<String>
+{
+    match &exif.get_field( tag, In::PRIMARY )?.value
+    {
+        Value::Ascii( strings ) =>
+        {
+            let string = String::from_utf8_lossy( strings.first()? ).trim().to_owned();
+            ( !string.is_empty() ).then_some( string )
+        }
+        _ => None,
+    }
+}
+
+/// Rational at an index of a field, `None` if it is not finite ( e.g. a zero denominator ).
+fn rational( exif: &Exif, tag: Tag, index: usize ) -> Option<f64>
+{
+    let value = match &exif.get_field( tag, In::PRIMARY )?.value
+    {
+        Value::Rational( rationals ) => rationals.get( index )?.to_f64(),
+        Value::SRational( rationals ) => rationals.get( index )?.to_f64(),
+        _ => return None,
+    };
+
+    value.is_finite().then_some( value )
+}
+
+/// Date the photo was taken, with the offset of the camera if recorded, in UTC otherwise.
+fn taken_at( exif: &Exif ) -> Option<DateTime<Utc>>
+{
+    let field = exif.get_field( Tag::DateTimeOriginal, In::PRIMARY )?;
+
+    let mut date_time = match &field.value
+    {
+        Value::Ascii( strings ) => exif::DateTime::from_ascii( strings.first()? ).ok()?,
+        _ => return None,
+    };
+
+    if let Some( Value::Ascii( strings ) ) =
+        exif.get_field( Tag::OffsetTimeOriginal, In::PRIMARY ).map( |field| &field.value )
+    {
+        if let Some( offset ) = strings.first()
+        {
+            let _ = date_time.parse_offset( offset );
+        }
+    }
+
+    let naive = NaiveDate::from_ymd_opt(
+        i32::from( date_time.year ),
+        u32::from( date_time.month ),
+        u32::from( date_time.day ),
+    )?
+    .and_hms_nano_opt(
+        u32::from( date_time.hour ),
+        u32::from( date_time.minute ),
+        u32::from( date_time.second ),
+        date_time.nanosecond.unwrap_or( 0 ),
+    )?;
+
+    let offset = FixedOffset::east_opt( i32::from( date_time.offset.unwrap_or( 0 ) ) * 60 )?;
+
+    offset
+        .from_local_datetime( &naive )
+        .single()
+        .map( |taken_at| taken_at.with_timezone( &Utc ) )
+}
+
+fn gps_position( exif: &Exif ) -> Option<GpsPosition>
+{
+    let latitude = gps_degrees( exif, Tag::GPSLatitude, Tag::GPSLatitudeRef, 'S' )?;
+    let longitude = gps_degrees( exif, Tag::GPSLongitude, Tag::GPSLongitudeRef, 'W' )?;
+
+    // A reference of 1 means below sea level.
+    let altitude_m = rational( exif, Tag::GPSAltitude, 0 ).map( |altitude| {
+        match exif
+            .get_field( Tag::GPSAltitudeRef, In::PRIMARY )
+            .and_then( |field| field.value.get_uint( 0 ) )
+        {
+            Some( 1 ) => -altitude,
+            _ => altitude,
+        }
+    } );
+
+    Some( GpsPosition {
+        latitude,
+        longitude,
+        altitude_m,
+    } )
+}
+
+/// Signed decimal degrees of a GPS coordinate stored as degrees, minutes and seconds.
+fn gps_degrees( exif: &Exif, tag: Tag, reference_tag: Tag, negative_reference: char ) -> Option<f64>
+{
+    let degrees = rational( exif, tag, 0 )?;
+    let minutes = rational( exif, tag, 1 ).unwrap_or( 0.0 );
+    let seconds = rational( exif, tag, 2 ).unwrap_or( 0.0 );
+    let value = degrees + minutes / 60.0 + seconds / 3600.0;
+
+    let is_negative = ascii( exif, reference_tag )
+        .is_some_and( |reference| reference.starts_with( negative_reference ) );
+
+    Some( if is_negative { -value } else { value } )
+}
+
+/// Finds the XMP packet at the start of a file. XMP is stored as plain text on every format.
+fn read_xmp_packet( path: &Path ) -> Option<String>
+{
+    let mut bytes = Vec::new();
+    File::open( path ).ok()?.take( XMP_SCAN_BYTES ).read_to_end( &mut bytes ).ok()?;
+
+    let start = find( &bytes, b"<x:xmpmeta" )?;
+    let end = start + find( &bytes[start..], b"</x:xmpmeta>" )?;
+
+    String::from_utf8( bytes[start..end].to_vec() ).ok()
+}
+
+/// Simple XMP property, either as an attribute ( `name="value"` ) or an element ( `<name>value</name>` ).
+fn xmp_property( xmp: &str, name: &str ) -> Option<String>
+{
+    let value = if let Some( start ) = xmp.find( &format!( "{name}=\"" ) )
+    {
+        let value = &xmp[start + name.len() + 2..];
+        &value[..value.find( '"' )?]
+    }
+    else
+    {
+        let start = xmp.find( &format!( "<{name}>" ) )? + name.len() + 2;
+        let value = &xmp[start..];
+        &value[..value.find( '<' )?]
+    };
+
+    let value = value.trim();
+
+    ( !value.is_empty() ).then( || value.to_owned() )
+}
+
+fn find( haystack: &[u8], needle: &[u8] ) -> Option<usize>
+{
+    haystack.windows( needle.len() ).position( |window| window == needle )
+}
+
+#[cfg( test )]
+#[allow( non_snake_case )]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn xmp_property__attribute_and_element__found()
+    {
+        let xmp = r#"<x:xmpmeta><rdf:Description xmp:Rating="4" aux:Lens="">
+            <exifEX:LensModel> 35mm F1.4 </exifEX:LensModel></rdf:Description></x:xmpmeta>"#;
+
+        assert_eq!( xmp_property( xmp, "xmp:Rating" ), Some( "4".to_owned() ) );
+        assert_eq!( xmp_property( xmp, "exifEX:LensModel" ), Some( "35mm F1.4".to_owned() ) );
+        assert_eq!( xmp_property( xmp, "aux:Lens" ), None );
+        assert_eq!( xmp_property( xmp, "dc:title" ), None );
+    }
+}
@@ -0,0 +1,217 @@
+use super::images::{self, DerivativeOptions};
+use crate::{services::features::domain::by_features::photos::PhotoDerivative, Result};
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use uuid::Uuid;
+
+/// Directory where the photo files are stored.
+///
+/// Each photo has its own directory named after its id, holding the original file
+/// ( `original.<extension>` ) and its derivatives ( `<size>.<extension>` ). Uploads are first written
+/// to the `.tmp` directory and then moved.
+#[derive(Debug, Clone)]
+pub struct PhotoStorage
+{
+    dir:                PathBuf,
+    derivative_options: Arc<DerivativeOptions>,
+}
+
+impl PhotoStorage
+{
+    #[must_use]
+    pub fn new( dir: &str, derivative_options: DerivativeOptions ) -> Self
+    {
+        Self {
+            dir:                PathBuf::from( dir ),
+            derivative_options: Arc::new( derivative_options ),
+        }
+    }
+
+    #[must_use]
+    pub fn dir( &self ) -> &Path { &self.dir }
+
+    /// Directory of the files of a photo.
+    #[must_use]
+    pub fn photo_dir( &self, photo_id: Uuid ) -> PathBuf { self.dir.join( photo_id.to_string() ) }
+
+    /// File name of the original file of a photo.
+    #[must_use]
+    pub fn original_file_name( extension: &str ) -> String { format!( "original.{extension}" ) }
+
+    /// Creates a new temporary file path for an upload, creating the temporary directory if missing.
+    ///
+    /// # Errors
+    ///
+    /// If the temporary directory can't be created.
+    pub async fn new_temp_path( &self ) -> Result<PathBuf>
+    {
+        let temp_dir = self.dir.join( ".tmp" );
+        tokio::fs::create_dir_all( &temp_dir ).await?;
+
+        Ok( temp_dir.join( format!( "{}.part", Uuid::new_v4() ) ) )
+    }
+
+    /// Moves a temporary file to the directory of a photo with the given file name.
+    ///
+    /// # Errors
+    ///
+    /// If the photo directory can't be created or the file can't be moved.
+    pub async fn persist( &self, temp_path: &Path, photo_id: Uuid, file_name: &str ) -> Result<PathBuf>
+    {
+        let photo_dir = self.photo_dir( photo_id );
+        tokio::fs::create_dir_all( &photo_dir ).await?;
+
+        let path = photo_dir.join( file_name );
+        tokio::fs::rename( temp_path, &path ).await?;
+
+        Ok( path )
+    }
+
+    /// Generates the derivatives of the original file of a photo, replacing the previous ones.
+    ///
+    /// The derivatives are generated on a temporary directory and only then moved to the photo directory, so the
+    /// previous ones are kept if the generation fails.
+    ///
+    /// # Errors
+    ///
+    /// If the new derivatives can't be generated or moved.
+    pub async fn generate_derivatives( &self, photo_id: Uuid, original_file_name: &str ) -> Result<Vec<PhotoDerivative>>
+    {
+        let photo_dir = self.photo_dir( photo_id );
+        let original = photo_dir.join( original_file_name );
+        let staging_dir = self.dir.join( ".tmp" ).join( Uuid::new_v4().to_string() );
+        let options = self.derivative_options.clone();
+
+        tokio::task::spawn_blocking( move || {
+            std::fs::create_dir_all( &staging_dir )?;
+            let result = images::generate_derivatives( &original, &staging_dir, &options )
+                .and_then( |derivatives| replace_derivatives( &photo_dir, &original, &staging_dir, derivatives ) );
+            let _ = std::fs::remove_dir_all( &staging_dir );
+
+            result
+        } )
+        .await?
+    }
+
+    /// Removes all the files of a photo. Does nothing if there are none.
+    ///
+    /// # Errors
+    ///
+    /// If the photo directory exists but can't be removed.
+    pub async fn remove( &self, photo_id: Uuid ) -> Result<()>
+    {
+        match tokio::fs::remove_dir_all( self.photo_dir( photo_id ) ).await
+        {
+            Err( err ) if err.kind() != std::io::ErrorKind::NotFound => Err( err.into() ),
+            _ => Ok( () ),
+        }
+    }
+}
+
+/// Moves the derivatives generated on the staging directory to the photo directory, overwriting the previous ones
+/// with the same file name, then removes the previous ones not generated anymore.
+fn replace_derivatives(
+    photo_dir: &Path,
+    original: &Path,
+    staging_dir: &Path,
+    derivatives: Vec<PhotoDerivative>,
+) -> Result<Vec<PhotoDerivative>>
+{
+    for derivative in &derivatives
+    {
+        std::fs::rename( staging_dir.join( &derivative.file_name ), photo_dir.join( &derivative.file_name ) )?;
+    }
+
+    // Every file other than the original and the new derivatives is a stale derivative. Failing to remove one
+    // only leaves an unused file behind, the new derivatives are already in place.
+    for entry in std::fs::read_dir( photo_dir )?
+    {
+        let path = entry?.path();
+        let is_derivative = derivatives
+            .iter()
+            .any( |derivative| path.file_name() == Some( derivative.file_name.as_ref() ) );
+
+        if path != original && !is_derivative
+        {
+            if let Err( err ) = std::fs::remove_file( &path )
+            {
+                tracing::warn!( "Failed to remove the stale derivative {}: {:?}", path.display(), err );
+            }
+        }
+    }
+
+    Ok( derivatives )
+}
+
+#[cfg( test )]
+#[allow( non_snake_case )]
+mod tests
+{
+    use super::*;
+    use images::{DerivativeFormat, DerivativeSize};
+
+    use image::RgbImage;
+
+    /// Storage on a new temporary directory, with a photo holding an original and a previous derivative.
+    fn storage_with_photo( original: impl FnOnce( &Path ) ) -> ( PhotoStorage, Uuid )
+    {
+        let dir = std::env::temp_dir().join( format!( "backend-storage-{}", Uuid::new_v4() ) );
+        let storage = PhotoStorage::new( dir.to_str().unwrap(), DerivativeOptions {
+            sizes:   vec![DerivativeSize { name: "thumbnail".to_owned(), max_dimension: 10 }],
+            formats: vec![DerivativeFormat::Jpeg],
+            quality: 80,
+        } );
+        let photo_id = Uuid::new_v4();
+        let photo_dir = storage.photo_dir( photo_id );
+
+        std::fs::create_dir_all( &photo_dir ).unwrap();
+        original( &photo_dir.join( "original.png" ) );
+        std::fs::write( photo_dir.join( "small.jpg" ), "previous derivative" ).unwrap();
+
+        ( storage, photo_id )
+    }
+
+    fn file_names( dir: &Path ) -> Vec<String>
+    {
+        let mut file_names = std::fs::read_dir( dir )
+            .unwrap()
+            .map( |entry| entry.unwrap().file_name().to_string_lossy().into_owned() )
+            .collect::<Vec<_>>();
+        file_names.sort();
+
+        file_names
+    }
+
+    #[tokio::test]
+    async fn generate_derivatives__valid_original__previous_derivatives_replaced()
+    {
+        let ( storage, photo_id ) = storage_with_photo( |path| RgbImage::new( 40, 20 ).save( path ).unwrap() );
+
+        let derivatives = storage.generate_derivatives( photo_id, "original.png" ).await.unwrap();
+        let photo_files = file_names( &storage.photo_dir( photo_id ) );
+        let temp_files = file_names( &storage.dir().join( ".tmp" ) );
+        std::fs::remove_dir_all( storage.dir() ).unwrap();
+
+        assert_eq!( derivatives.len(), 1 );
+        assert_eq!( photo_files, vec!["original.png", "thumbnail.jpg"] );
+        assert!( temp_files.is_empty() );
+    }
+
+    #[tokio::test]
+    async fn generate_derivatives__undecodable_original__previous_derivatives_kept()
+    {
+        let ( storage, photo_id ) = storage_with_photo( |path| std::fs::write( path, "not an image" ).unwrap() );
+
+        let result = storage.generate_derivatives( photo_id, "original.png" ).await;
+        let photo_files = file_names( &storage.photo_dir( photo_id ) );
+        let temp_files = file_names( &storage.dir().join( ".tmp" ) );
+        std::fs::remove_dir_all( storage.dir() ).unwrap();
+
+        assert!( result.is_err() );
+        assert_eq!( photo_files, vec!["original.png", "small.jpg"] );
+        assert!( temp_files.is_empty() );
+    }
+}
@@ -1,4 +1,3 @@
-#![feature( once_cell )]
 #![deny( clippy::all )]
 #![warn( clippy::pedantic )]
 #![warn( clippy::nursery )]
@@ -6,35 +5,38 @@
 #![warn( clippy::perf )]
 
 // Modules.
+pub mod app;
+pub mod health;
 pub mod services;
 pub mod settings;
+pub mod tls;
 
 // Crate use re-exports.
 pub use color_eyre::eyre::Result;
 
+use app::{metrics_router, shutdown_signal, App};
+use color_eyre::eyre::WrapErr;
 use monitoring::logger;
-
-use axum::{
-    body::{Body, StreamBody},
-    extract::State,
-    handler::Handler,
-    http,
-    http::Request,
-    response::IntoResponse,
-    routing::{get, get_service},
+use services::{
+    features::infrastructure::{by_features::Repositories, database, images::DerivativeOptions, storage::PhotoStorage},
+    routes,
+    validators::uploads::UploadRules,
 };
+
 use std::{
-    convert::Infallible,
-    net::{IpAddr, Ipv6Addr, SocketAddr},
+    net::{IpAddr, Ipv6Addr, SocketAddr, TcpListener},
+    path::Path,
     str::FromStr,
+    sync::Arc,
+    time::Duration,
 };
 
-use futures::stream::{self, StreamExt};
-use tower_http::{
-    compression::CompressionLayer,
-    services::{ServeDir, ServeFile},
-};
-
+/// Starts the logger with the logger settings, the returned guards flush the logs when dropped.
+///
+/// # Panics
+///
+/// If the log level can't be parsed or the logger files settings are missing.
+#[must_use]
 pub fn start_logs( log_level: &str ) -> ( Option<logger::WorkerGuard>, Option<logger::WorkerGuard> )
 {
     let mut log_output_types = Vec::new();
@@ -56,7 +58,7 @@ pub fn start_logs( log_level: &str ) -> ( Option<logger::WorkerGuard>, Option<lo
                 .files_prefix()
                 .as_ref()
                 .expect( "Failed to get logger files prefix" ),
-        } )
+        } );
     }
 
     logger::init(
@@ -65,111 +67,214 @@ pub fn start_logs( log_level: &str ) -> ( Option<logger::WorkerGuard>, Option<lo
     )
 }
 
-#[cfg( feature = "ssr" )]
-#[derive(Clone)]
-struct YewRendererState
+async fn connect_database() -> Result<sqlx::SqlitePool>
 {
-    index_html_before: String,
-    index_html_after:  String,
+    database::connect(
+        settings::DATABASE.url(),
+        *settings::DATABASE.max_connections(),
+        Duration::from_secs( *settings::DATABASE.acquire_timeout_secs() ),
+    )
+    .await
 }
 
-#[cfg( feature = "ssr" )]
-async fn render_yew_app( State( state ): State<YewRendererState>, req: Request<Body> ) -> impl IntoResponse
+/// Applies the pending database migrations and exits.
+///
+/// # Errors
+///
+/// If the database can't be opened or a migration fails.
+///
+/// # Panics
+///
+/// If the tokio runtime can't be built.
+#[tokio::main]
+pub async fn migrate_database() -> Result<()>
 {
-    let req_url = req.uri().path().to_string();
-    let req_queries: Vec<( String, String )> = qstring::QString::from( req.uri().query().unwrap_or( "" ) ).into();
-
-    let renderer = yew::ServerRenderer::<frontend::ServerApp>::with_props( move || frontend::ServerAppProps {
-        request_data: frontend::RequestData {
-            url:     req_url,
-            queries: req_queries,
-        },
-    } );
+    let pool = connect_database().await?;
+    database::migrate( &pool ).await?;
+    pool.close().await;
 
-    StreamBody::new(
-        stream::once( async move { state.index_html_before } )
-            .chain( renderer.render_stream() )
-            .chain( stream::once( async move { state.index_html_after } ) )
-            .map( Result::<_, Infallible>::Ok ),
-    )
+    Ok( () )
 }
 
-#[cfg( feature = "ssr" )]
-async fn get_yew_render_state( static_dir: &str ) -> YewRendererState
+/// Regenerates the derivatives of all the stored photos and exits, e.g. after changing the derivative
+/// settings.
+///
+/// # Errors
+///
+/// If the database can't be opened or the photos can't be listed or updated. Photos whose derivatives
+/// fail to generate are logged and skipped.
+///
+/// # Panics
+///
+/// If the tokio runtime can't be built.
+#[tokio::main]
+pub async fn regenerate_derivatives() -> Result<()>
 {
-    // Get index file.
-    let index_html_s = tokio::fs::read_to_string( format!( "{}/index.html", static_dir ) )
-        .await
-        .expect( "Failed to read index.html" );
-    let ( index_html_before, index_html_after ) = index_html_s.split_once( "<body>" ).unwrap();
-    let mut index_html_before = index_html_before.to_owned();
-    index_html_before.push_str( "<body>" );
-
-    let index_html_after = index_html_after.to_owned();
-
-    let state = YewRendererState {
-        index_html_before,
-        index_html_after,
-    };
+    let pool = connect_database().await?;
+    let repositories = Repositories::sqlite( &pool );
+    let photo_storage = photo_storage();
+
+    for photo in repositories.photos.list( None ).await?
+    {
+        let file_name = match photo.file
+        {
+            Some( file ) => file.file_name,
+            None => continue,
+        };
 
-    state
+        match photo_storage.generate_derivatives( photo.id, &file_name ).await
+        {
+            Ok( derivatives ) =>
+            {
+                repositories.photos.set_derivatives( photo.id, &derivatives ).await?;
+                tracing::info!( "Generated {} derivatives of photo {}.", derivatives.len(), photo.id );
+            }
+            Err( err ) => tracing::warn!( "Failed to generate the derivatives of photo {}: {:?}", photo.id, err ),
+        }
+    }
+
+    pool.close().await;
+
+    Ok( () )
+}
+
+fn photo_storage() -> PhotoStorage
+{
+    PhotoStorage::new( settings::STORAGE.photos_dir(), DerivativeOptions {
+        sizes:   settings::STORAGE.derivative_sizes().clone(),
+        formats: settings::STORAGE.derivative_formats().clone(),
+        quality: *settings::STORAGE.derivative_quality(),
+    } )
 }
 
+/// Starts the server with the settings and blocks until it is shut down by a signal, see [`App`] to embed it instead.
+///
+/// # Errors
+///
+/// If the server fails or the in-flight requests don't finish before the shutdown timeout.
+///
+/// # Panics
+///
+/// If the tokio runtime can't be built.
 #[tokio::main]
-pub async fn start_server( addr: &str, port: u16, static_dir: &str, assets_dir: &str )
+pub async fn start_server(
+    addr: &str,
+    port: u16,
+    static_dir: &str,
+    assets_dir: &str,
+    metrics_port: Option<u16>,
+) -> Result<()>
 {
-    let br_compression = CompressionLayer::new().br( true ).no_gzip().no_deflate();
+    // Database.
+    let db_pool = connect_database().await.wrap_err( "Failed to connect to the database" )?;
 
-    // Api router.
-    let mut app = axum::Router::new().route( "/api/hello", get( hello ).layer( br_compression.clone() ) );
+    if *settings::DATABASE.run_migrations()
+    {
+        database::migrate( &db_pool ).await.wrap_err( "Failed to apply database migrations" )?;
+    }
+
+    // Api state.
+    let api_state = routes::ApiState {
+        repositories:  Repositories::sqlite( &db_pool ),
+        photo_storage: photo_storage(),
+        upload_rules:  Arc::new( UploadRules {
+            max_bytes:          *settings::STORAGE.max_upload_bytes(),
+            allowed_mime_types: settings::STORAGE.allowed_mime_types().clone(),
+        } ),
+    };
 
-    #[cfg( feature = "ssr" )]
+    // Metrics route, served on the admin port if one is set.
+    if let Some( metrics_port ) = metrics_port
     {
-        // Yew render service for SSR.
-        let state = get_yew_render_state( static_dir ).await;
-        let renderer = render_yew_app.layer( br_compression.clone() ).with_state( state );
-
-        // Robot.txt file get service.
-        let robots_file = get_service( ServeFile::new( format!( "{}/robots.txt", assets_dir ) ) )
-            .layer( br_compression.clone() )
-            .handle_error( handle_error );
-
-        // Static files directory get service.
-        let serve_static_dir =
-            get_service( ServeDir::new( static_dir ).precompressed_br() ).handle_error( handle_error );
-
-        // Assets files directory get service.
-        let serve_assets_dir =
-            get_service( ServeDir::new( assets_dir ).precompressed_br() ).handle_error( handle_error );
-
-        // Routes.
-        app = app
-            .route( "/robots.txt", robots_file )
-            .nest_service( "/static", serve_static_dir.clone() )
-            .nest_service( "/assets", serve_assets_dir.clone() )
-            .fallback_service( renderer );
+        let metrics_sock_addr = SocketAddr::from( (
+            IpAddr::from_str( addr ).unwrap_or( IpAddr::V6( Ipv6Addr::LOCALHOST ) ),
+            metrics_port,
+        ) );
+
+        let metrics_listener =
+            TcpListener::bind( metrics_sock_addr ).wrap_err( "Unable to bind the metrics server address" )?;
+
+        tracing::info!( "Serving metrics on http://{}/metrics", metrics_sock_addr );
+
+        spawn_server( "metrics", metrics_listener, metrics_router() )?;
+    }
+
+    let mut app_builder = App::builder( api_state, static_dir, assets_dir )
+        .run_env( settings::GENERAL.run_env().clone() )
+        .serve_metrics( metrics_port.is_none() )
+        .health_check( "database", move || {
+            let db_pool = db_pool.clone();
+
+            async move { database::ping( &db_pool ).await }
+        } );
+
+    // The log files are written by a background worker, check that it can still create them.
+    if let ( true, Some( logs_dir ) ) = ( *settings::LOGGER.is_file_emitted(), settings::LOGGER.files_directory() )
+    {
+        app_builder = app_builder.health_check( "logs_dir_writable", move || {
+            let logs_dir = logs_dir.clone();
+
+            async move { health::check_dir_writable( Path::new( &logs_dir ) ).await }
+        } );
     }
 
-    // Http tracing logs middleware layer.
-    let app = logger::middleware_http_tracing( app );
+    let app = app_builder.build().await.wrap_err( "Failed to build the app" )?;
 
     // Serve server.
     let sock_addr = SocketAddr::from( (
         IpAddr::from_str( addr ).unwrap_or( IpAddr::V6( Ipv6Addr::LOCALHOST ) ),
         port,
     ) );
+    let listener = TcpListener::bind( sock_addr ).wrap_err( "Unable to bind the server address" )?;
+    let shutdown_timeout = Duration::from_secs( *settings::SERVER.shutdown_timeout_secs() );
+
+    if let Some( tls_configs ) = settings::SERVER.tls()
+    {
+        // Certificate reloaded when renewed.
+        let resolver = Arc::new( tls::CertificateResolver::new( tls_configs.cert_path(), tls_configs.key_path() )? );
+        let _certificate_watcher = resolver.watch( Duration::from_secs( *tls_configs.reload_interval_secs() ) );
+        let tls_config = tls::server_config( resolver, *tls_configs.min_version(), tls_configs.alpn_protocols() )?;
+
+        // Plain http listener redirecting to https.
+        if let Some( redirect_port ) = *tls_configs.redirect_port()
+        {
+            let redirect_sock_addr = SocketAddr::from( ( sock_addr.ip(), redirect_port ) );
+
+            let redirect_listener = TcpListener::bind( redirect_sock_addr )
+                .wrap_err( "Unable to bind the redirect server address" )?;
+
+            tracing::info!( "Redirecting http://{} to https", redirect_sock_addr );
 
-    tracing::info!( "Listening on https://{}", sock_addr );
+            spawn_server( "redirect", redirect_listener, tls::redirect_router( port ) )?;
+        }
 
-    axum::Server::bind( &sock_addr )
-        .serve( app.into_make_service() )
-        .await
-        .expect( "Unable to start server" );
+        tracing::info!( "Listening on https://{}", sock_addr );
+
+        app.serve_tls_with_shutdown( listener, Arc::new( tls_config ), shutdown_signal(), shutdown_timeout ).await?;
+    }
+    else
+    {
+        tracing::info!( "Listening on http://{}", sock_addr );
+
+        app.serve_with_shutdown( listener, shutdown_signal(), shutdown_timeout ).await?;
+    }
+
+    tracing::info!( "Server stopped." );
+
+    Ok( () )
 }
 
-async fn handle_error( _err: std::io::Error ) -> impl IntoResponse
+/// Serves a router on a listener in the background, logging the error that stops it.
+fn spawn_server( name: &'static str, listener: TcpListener, router: axum::Router ) -> Result<()>
 {
-    ( http::StatusCode::INTERNAL_SERVER_ERROR, "Something went wrong..." )
-}
+    let server = axum::Server::from_tcp( listener )?.serve( router.into_make_service() );
 
-async fn hello() -> impl IntoResponse { "hello from the backend!" }
+    tokio::spawn( async move {
+        if let Err( err ) = server.await
+        {
+            tracing::error!( "The {} server stopped: {:?}", name, err );
+        }
+    } );
+
+    Ok( () )
+}
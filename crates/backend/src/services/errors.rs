@@ -0,0 +1,205 @@
+use crate::services::validators::ValidationErrors;
+
+use common::dtos::errors::{ErrorCode, FieldError, Problem, PROBLEM_JSON_CONTENT_TYPE};
+use monitoring::logger;
+
+use axum::{
+    extract::{
+        multipart::{MultipartError, MultipartRejection},
+        rejection::{JsonRejection, PathRejection, QueryRejection},
+    },
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use color_eyre::eyre::eyre;
+use std::fmt;
+
+/// Detail of the internal errors, their cause is only logged.
+const INTERNAL_ERROR_DETAIL: &str = "Something went wrong...";
+
+/// Error of the api handlers, answered as an RFC 7807 `application/problem+json` body with the id of the request.
+#[derive(Debug)]
+pub enum ApiError
+{
+    /// The request is malformed, the reason is answered.
+    BadRequest( String ),
+    /// The requested resource doesn't exist.
+    NotFound,
+    /// The request body is over its size limit, the reason is answered.
+    PayloadTooLarge( String ),
+    /// Fields of the request failed validation.
+    Validation( Vec<FieldError> ),
+    /// Unexpected error, logged and answered without its cause.
+    Internal( color_eyre::Report ),
+}
+
+impl ApiError
+{
+    #[allow( clippy::needless_pass_by_value )]
+    pub fn bad_request( err: impl fmt::Display ) -> Self { Self::BadRequest( err.to_string() ) }
+
+    pub fn internal( err: impl Into<color_eyre::Report> ) -> Self { Self::Internal( err.into() ) }
+
+    /// Error of a rejected extractor, a bad request with its reason unless the rejection is a server error or a body
+    /// over its size limit.
+    fn rejection( status: StatusCode, reason: String ) -> Self
+    {
+        if status.is_server_error()
+        {
+            Self::Internal( eyre!( reason ) )
+        }
+        else if status == StatusCode::PAYLOAD_TOO_LARGE
+        {
+            Self::PayloadTooLarge( reason )
+        }
+        else
+        {
+            Self::BadRequest( reason )
+        }
+    }
+
+    #[must_use]
+    pub const fn code( &self ) -> ErrorCode
+    {
+        match self
+        {
+            Self::BadRequest( _ ) => ErrorCode::BadRequest,
+            Self::NotFound => ErrorCode::NotFound,
+            Self::PayloadTooLarge( _ ) => ErrorCode::PayloadTooLarge,
+            Self::Validation( _ ) => ErrorCode::ValidationFailed,
+            Self::Internal( _ ) => ErrorCode::Internal,
+        }
+    }
+
+    /// Problem details answered for the error.
+    #[must_use]
+    pub fn problem( self ) -> Problem
+    {
+        let code = self.code();
+        let mut problem = match self
+        {
+            Self::BadRequest( reason ) | Self::PayloadTooLarge( reason ) => Problem::new( code, Some( reason ) ),
+            Self::NotFound => Problem::new( code, None ),
+            Self::Validation( errors ) => Problem {
+                errors,
+                ..Problem::new( code, None )
+            },
+            Self::Internal( err ) =>
+            {
+                tracing::error!( "{:?}", err );
+                Problem::new( code, Some( INTERNAL_ERROR_DETAIL.to_owned() ) )
+            }
+        };
+
+        problem.request_id = logger::current_request_id();
+        problem
+    }
+}
+
+impl From<color_eyre::Report> for ApiError
+{
+    fn from( err: color_eyre::Report ) -> Self { Self::Internal( err ) }
+}
+
+impl From<ValidationErrors> for ApiError
+{
+    fn from( errors: ValidationErrors ) -> Self { Self::Validation( errors.into_inner() ) }
+}
+
+impl From<JsonRejection> for ApiError
+{
+    fn from( rejection: JsonRejection ) -> Self { Self::rejection( rejection.status(), rejection.body_text() ) }
+}
+
+impl From<PathRejection> for ApiError
+{
+    fn from( rejection: PathRejection ) -> Self { Self::rejection( rejection.status(), rejection.body_text() ) }
+}
+
+impl From<QueryRejection> for ApiError
+{
+    fn from( rejection: QueryRejection ) -> Self { Self::rejection( rejection.status(), rejection.body_text() ) }
+}
+
+impl From<MultipartRejection> for ApiError
+{
+    fn from( rejection: MultipartRejection ) -> Self { Self::rejection( rejection.status(), rejection.body_text() ) }
+}
+
+/// Error while reading the fields of a multipart form, e.g. when its body is over the size limit.
+impl From<MultipartError> for ApiError
+{
+    fn from( err: MultipartError ) -> Self { Self::rejection( err.status(), err.body_text() ) }
+}
+
+impl IntoResponse for ApiError
+{
+    fn into_response( self ) -> Response
+    {
+        let problem = self.problem();
+        let status_code = StatusCode::from_u16( problem.status ).unwrap_or( StatusCode::INTERNAL_SERVER_ERROR );
+
+        (
+            status_code,
+            [( header::CONTENT_TYPE, HeaderValue::from_static( PROBLEM_JSON_CONTENT_TYPE ) )],
+            Json( problem ),
+        )
+            .into_response()
+    }
+}
+
+#[cfg( test )]
+#[allow( non_snake_case )]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn problem__internal__hides_the_cause()
+    {
+        let problem = ApiError::Internal( eyre!( "database is locked" ) ).problem();
+
+        assert_eq!( problem.code, ErrorCode::Internal );
+        assert_eq!( problem.status, 500 );
+        assert_eq!( problem.detail.as_deref(), Some( INTERNAL_ERROR_DETAIL ) );
+        assert!( !format!( "{problem:?}" ).contains( "database is locked" ) );
+    }
+
+    #[test]
+    fn problem__validation__lists_the_field_errors()
+    {
+        let mut errors = ValidationErrors::default();
+        errors.add( "title", "must not be blank" );
+
+        let problem = ApiError::from( errors ).problem();
+
+        assert_eq!( problem.code, ErrorCode::ValidationFailed );
+        assert_eq!( problem.status, 422 );
+        assert_eq!( problem.errors, vec![ FieldError {
+            field:   "title".to_owned(),
+            message: "must not be blank".to_owned(),
+        } ] );
+    }
+
+    #[test]
+    fn rejection__client_and_server_errors__bad_request_or_internal()
+    {
+        let client_error = ApiError::rejection( StatusCode::UNSUPPORTED_MEDIA_TYPE, "Expected json".to_owned() );
+        let server_error = ApiError::rejection( StatusCode::INTERNAL_SERVER_ERROR, "No paths parameters".to_owned() );
+
+        assert_eq!( client_error.problem().detail.as_deref(), Some( "Expected json" ) );
+        assert_eq!( server_error.code(), ErrorCode::Internal );
+    }
+
+    #[test]
+    fn rejection__payload_too_large__payload_too_large_problem()
+    {
+        let rejection = ApiError::rejection( StatusCode::PAYLOAD_TOO_LARGE, "Length limit exceeded".to_owned() );
+        let problem = rejection.problem();
+
+        assert_eq!( problem.code, ErrorCode::PayloadTooLarge );
+        assert_eq!( problem.status, 413 );
+        assert_eq!( problem.detail.as_deref(), Some( "Length limit exceeded" ) );
+    }
+}
@@ -0,0 +1,29 @@
+//! Extractors of the api handlers, answering their rejections as [`ApiError`] problem details rather than the plain
+//! text ones of axum.
+
+use crate::services::errors::ApiError;
+
+use axum::{
+    extract::{FromRequest, FromRequestParts},
+    response::{IntoResponse, Response},
+};
+
+/// Json request body, also answered as a json response.
+#[derive(Debug, FromRequest)]
+#[from_request( via( axum::Json ), rejection( ApiError ) )]
+pub struct Json<T>( pub T );
+
+impl<T: serde::Serialize> IntoResponse for Json<T>
+{
+    fn into_response( self ) -> Response { axum::Json( self.0 ).into_response() }
+}
+
+/// Path parameters.
+#[derive(Debug, FromRequestParts)]
+#[from_request( via( axum::extract::Path ), rejection( ApiError ) )]
+pub struct Path<T>( pub T );
+
+/// Query string parameters.
+#[derive(Debug, FromRequestParts)]
+#[from_request( via( axum::extract::Query ), rejection( ApiError ) )]
+pub struct Query<T>( pub T );
@@ -0,0 +1,220 @@
+//! Content negotiation of image files by the `Accept` header.
+//!
+//! Image files can have sibling variants in other formats next to them, with the same name and a
+//! different extension ( e.g. `test.jpg`, `test.webp` and `test.avif` ). A request for any of them
+//! is answered with the best variant the client accepts, or with the requested file if there is
+//! none.
+
+use axum::http::{header, uri::PathAndQuery, HeaderMap, HeaderValue, Request, Response, Uri};
+use std::{
+    future::Future,
+    path::{Component, Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower::{Layer, Service};
+
+/// Extensions of the image files that are negotiated.
+const IMAGE_EXTENSIONS: [&str; 6] = ["jpg", "jpeg", "png", "gif", "webp", "avif"];
+
+/// Variants offered to the clients that accept them, most preferred first.
+const VARIANTS: [( &str, &str ); 2] = [( "image/avif", "avif" ), ( "image/webp", "webp" )];
+
+/// Tower layer that rewrites image requests to the best variant on a directory.
+///
+/// Meant to be layered over a service that serves the same directory, e.g. a `ServeDir`.
+#[derive(Clone)]
+pub struct ImageNegotiationLayer
+{
+    dir: Arc<Path>,
+}
+
+impl ImageNegotiationLayer
+{
+    /// Creates a new layer negotiating the images of `dir`.
+    #[must_use]
+    pub fn new( dir: &str ) -> Self { Self { dir: Arc::from( Path::new( dir ) ) } }
+}
+
+impl<S> Layer<S> for ImageNegotiationLayer
+{
+    type Service = ImageNegotiationService<S>;
+
+    fn layer( &self, inner: S ) -> Self::Service
+    {
+        ImageNegotiationService {
+            inner,
+            dir: self.dir.clone(),
+        }
+    }
+}
+
+/// Tower service created by [`ImageNegotiationLayer`].
+#[derive(Clone)]
+pub struct ImageNegotiationService<S>
+{
+    inner: S,
+    dir:   Arc<Path>,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for ImageNegotiationService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+    type Response = S::Response;
+
+    fn poll_ready( &mut self, cx: &mut Context<'_> ) -> Poll<Result<(), Self::Error>> { self.inner.poll_ready( cx ) }
+
+    fn call( &mut self, mut request: Request<ReqBody> ) -> Self::Future
+    {
+        // Use the service that was driven to readiness and leave a fresh clone in its place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace( &mut self.inner, clone );
+        let dir = self.dir.clone();
+
+        Box::pin( async move {
+            let is_image = image_stem( request.uri().path() ).is_some();
+
+            if is_image
+            {
+                if let Some( uri ) = best_variant_uri( &dir, request.uri(), request.headers() ).await
+                {
+                    *request.uri_mut() = uri;
+                }
+            }
+
+            let mut response = inner.call( request ).await?;
+
+            // Caches must not share the response between clients accepting different formats.
+            if is_image
+            {
+                response
+                    .headers_mut()
+                    .append( header::VARY, HeaderValue::from_static( "accept" ) );
+            }
+
+            Ok( response )
+        } )
+    }
+}
+
+/// Returns the uri of the best variant of the requested image that exists and the client accepts,
+/// `None` if the requested file should be served as is.
+async fn best_variant_uri( dir: &Path, uri: &Uri, headers: &HeaderMap ) -> Option<Uri>
+{
+    let stem = image_stem( uri.path() )?;
+    let accept = headers.get( header::ACCEPT )?.to_str().ok()?;
+
+    for ( mime_type, extension ) in VARIANTS
+    {
+        if !accepts( accept, mime_type )
+        {
+            continue;
+        }
+
+        let variant_path = format!( "{stem}.{extension}" );
+
+        if variant_path == uri.path()
+        {
+            return None;
+        }
+
+        if tokio::fs::metadata( file_path( dir, &variant_path )? ).await.is_ok_and( |metadata| metadata.is_file() )
+        {
+            let path_and_query = match uri.query()
+            {
+                Some( query ) => format!( "{variant_path}?{query}" ),
+                None => variant_path,
+            };
+
+            let mut parts = uri.clone().into_parts();
+            parts.path_and_query = Some( PathAndQuery::try_from( path_and_query ).ok()? );
+
+            return Uri::from_parts( parts ).ok();
+        }
+    }
+
+    None
+}
+
+/// Returns the request path without its extension if it is an image, e.g. `/images/test`.
+fn image_stem( path: &str ) -> Option<&str>
+{
+    let ( stem, extension ) = path.rsplit_once( '.' )?;
+
+    ( !stem.ends_with( '/' ) && IMAGE_EXTENSIONS.contains( &extension.to_ascii_lowercase().as_str() ) )
+        .then_some( stem )
+}
+
+/// Returns the file of a request path on a directory, `None` if it could be outside of it.
+fn file_path( dir: &Path, request_path: &str ) -> Option<PathBuf>
+{
+    let relative_path = Path::new( request_path.trim_start_matches( '/' ) );
+
+    relative_path
+        .components()
+        .all( |component| matches!( component, Component::Normal( _ ) ) )
+        .then( || dir.join( relative_path ) )
+}
+
+/// Returns whether an `Accept` header value explicitly accepts a media type.
+///
+/// Wildcards such as `image/*` are ignored, browsers send them without supporting every format.
+fn accepts( accept: &str, mime_type: &str ) -> bool
+{
+    accept.split( ',' ).any( |media_range| {
+        let mut params = media_range.split( ';' ).map( str::trim );
+
+        params.next().is_some_and( |media_type| media_type.eq_ignore_ascii_case( mime_type ) )
+            && params
+                .filter_map( |param| param.strip_prefix( "q=" ) )
+                .all( |quality| quality.parse::<f32>().is_ok_and( |quality| quality > 0.0 ) )
+    } )
+}
+
+#[cfg( test )]
+#[allow( non_snake_case )]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn accepts__browser_accept__only_explicit_types()
+    {
+        let accept = "image/avif,image/webp,image/apng,image/svg+xml,image/*,*/*;q=0.8";
+
+        assert!( accepts( accept, "image/avif" ) );
+        assert!( accepts( accept, "image/webp" ) );
+        assert!( !accepts( "image/webp,image/*,*/*;q=0.8", "image/avif" ) );
+    }
+
+    #[test]
+    fn accepts__zero_quality__not_accepted()
+    {
+        assert!( !accepts( "image/avif;q=0, image/webp;q=0.5", "image/avif" ) );
+        assert!( accepts( "image/avif;q=0, image/webp;q=0.5", "image/webp" ) );
+    }
+
+    #[test]
+    fn image_stem__paths__only_images()
+    {
+        assert_eq!( image_stem( "/images/test.JPG" ), Some( "/images/test" ) );
+        assert_eq!( image_stem( "/images/.webp" ), None );
+        assert_eq!( image_stem( "/robots.txt" ), None );
+    }
+
+    #[test]
+    fn file_path__parent_components__none()
+    {
+        assert_eq!( file_path( Path::new( "assets" ), "/images/../../secret.webp" ), None );
+        assert_eq!(
+            file_path( Path::new( "assets" ), "/images/test.webp" ),
+            Some( PathBuf::from( "assets/images/test.webp" ) )
+        );
+    }
+}
@@ -1,5 +1,9 @@
 #[path = "../features/mod.rs"]
-mod features;
+pub mod features;
 
 // Modules
+pub mod errors;
+pub mod extractors;
+pub mod image_negotiation;
 pub mod routes;
+pub mod validators;
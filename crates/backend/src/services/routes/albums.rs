@@ -0,0 +1,145 @@
+use super::ApiState;
+use crate::services::{
+    errors::ApiError,
+    extractors::{Json, Path},
+    features::{
+        domain::by_features::albums::{Album, AlbumFields},
+        infrastructure::by_features::Repositories,
+    },
+    validators::{self, ValidationErrors},
+};
+
+use common::dtos::albums::{AlbumRequest, AlbumResponse};
+
+use axum::{extract::State, http::StatusCode, routing::get, Router};
+use uuid::Uuid;
+
+/// Album routes, to be nested under `/api/albums`.
+pub fn router() -> Router<ApiState>
+{
+    Router::new()
+        .route( "/", get( list_albums ).post( create_album ) )
+        .route( "/:id", get( get_album ).put( update_album ).delete( delete_album ) )
+}
+
+impl From<Album> for AlbumResponse
+{
+    fn from( album: Album ) -> Self
+    {
+        Self {
+            id:             album.id,
+            title:          album.title,
+            description:    album.description,
+            position:       album.position,
+            cover_photo_id: album.cover_photo_id,
+            created_at:     album.created_at,
+            updated_at:     album.updated_at,
+        }
+    }
+}
+
+async fn list_albums( State( repositories ): State<Repositories> ) -> Result<Json<Vec<AlbumResponse>>, ApiError>
+{
+    let albums = repositories.albums.list().await?;
+
+    Ok( Json( albums.into_iter().map( AlbumResponse::from ).collect() ) )
+}
+
+async fn get_album(
+    State( repositories ): State<Repositories>,
+    Path( id ): Path<Uuid>,
+) -> Result<Json<AlbumResponse>, ApiError>
+{
+    let album = repositories.albums.get( id ).await?;
+
+    album.map( |album| Json( album.into() ) ).ok_or( ApiError::NotFound )
+}
+
+async fn create_album(
+    State( repositories ): State<Repositories>,
+    Json( request ): Json<AlbumRequest>,
+) -> Result<( StatusCode, Json<AlbumResponse> ), ApiError>
+{
+    validators::albums::validate_album_request( &request )?;
+
+    // A new album has no photos to be used as cover.
+    if request.cover_photo_id.is_some()
+    {
+        let mut errors = ValidationErrors::default();
+        errors.add( "cover_photo_id", "must be a photo of the album" );
+
+        return Err( errors.into() );
+    }
+
+    let position = match request.position
+    {
+        Some( position ) => position,
+        None => repositories.albums.next_position().await?,
+    };
+
+    let album = Album::new( AlbumFields {
+        title:          request.title,
+        description:    request.description,
+        position,
+        cover_photo_id: None,
+    } );
+
+    repositories.albums.insert( &album ).await?;
+
+    Ok( ( StatusCode::CREATED, Json( album.into() ) ) )
+}
+
+async fn update_album(
+    State( repositories ): State<Repositories>,
+    Path( id ): Path<Uuid>,
+    Json( request ): Json<AlbumRequest>,
+) -> Result<Json<AlbumResponse>, ApiError>
+{
+    validators::albums::validate_album_request( &request )?;
+
+    let mut album = repositories.albums.get( id ).await?.ok_or( ApiError::NotFound )?;
+
+    // The cover must be one of the album photos.
+    if let Some( cover_photo_id ) = request.cover_photo_id
+    {
+        let cover_photo = repositories.photos.get( cover_photo_id ).await?;
+
+        if cover_photo.is_none_or( |photo| photo.album_id != Some( id ) )
+        {
+            let mut errors = ValidationErrors::default();
+            errors.add( "cover_photo_id", "must be a photo of the album" );
+
+            return Err( errors.into() );
+        }
+    }
+
+    album.update( AlbumFields {
+        title:          request.title,
+        description:    request.description,
+        position:       request.position.unwrap_or( album.position ),
+        cover_photo_id: request.cover_photo_id,
+    } );
+
+    if !repositories.albums.update( &album ).await?
+    {
+        return Err( ApiError::NotFound );
+    }
+
+    Ok( Json( album.into() ) )
+}
+
+async fn delete_album(
+    State( repositories ): State<Repositories>,
+    Path( id ): Path<Uuid>,
+) -> Result<StatusCode, ApiError>
+{
+    if !repositories.albums.delete( id ).await?
+    {
+        return Err( ApiError::NotFound );
+    }
+
+    // Photos of a deleted album are kept without album.
+    repositories.photos.detach_from_album( id ).await?;
+
+    Ok( StatusCode::NO_CONTENT )
+}
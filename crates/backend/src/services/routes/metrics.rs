@@ -0,0 +1,30 @@
+use monitoring::prometheus::{self, metrics};
+
+use axum::{http::header, response::IntoResponse};
+use std::sync::LazyLock;
+
+/// Registry owned by the backend with all the monitoring metrics registered.
+pub static REGISTRY: LazyLock<::prometheus::Registry> = LazyLock::new( || {
+    let registry = ::prometheus::Registry::new();
+
+    prometheus::add_metrics_to_registry(
+        &registry,
+        vec![
+            Box::new( metrics::INCOMING_REQUESTS.clone() ),
+            Box::new( metrics::CONNECTED_CLIENTS.clone() ),
+            Box::new( metrics::RESPONSE_CODE_COLLECTOR.clone() ),
+            Box::new( metrics::RESPONSE_TIME_COLLECTOR.clone() ),
+        ],
+    );
+
+    registry
+} );
+
+/// Serves the registry metrics in the prometheus text exposition format.
+pub async fn get_metrics() -> impl IntoResponse
+{
+    (
+        [( header::CONTENT_TYPE, prometheus::TEXT_FORMAT )],
+        prometheus::encode_metrics( &REGISTRY ),
+    )
+}
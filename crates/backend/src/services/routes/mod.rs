@@ -1 +1,21 @@
+// Modules.
+pub mod albums;
+pub mod metrics;
+pub mod photos;
 
+use crate::services::{
+    features::infrastructure::{by_features::Repositories, storage::PhotoStorage},
+    validators::uploads::UploadRules,
+};
+
+use axum::extract::FromRef;
+use std::sync::Arc;
+
+/// State shared by the api routes. Handlers extract the parts they need.
+#[derive(Clone, FromRef)]
+pub struct ApiState
+{
+    pub repositories:  Repositories,
+    pub photo_storage: PhotoStorage,
+    pub upload_rules:  Arc<UploadRules>,
+}
@@ -0,0 +1,511 @@
+use super::ApiState;
+use crate::services::{
+    errors::ApiError,
+    extractors::{Json, Path, Query},
+    features::{
+        domain::by_features::photos::{Photo, PhotoFields, PhotoFile, PhotoMetadata},
+        infrastructure::{by_features::Repositories, photo_metadata, storage::PhotoStorage},
+    },
+    validators::{
+        self,
+        uploads::{ImageMimeType, UploadRules, SNIFF_LEN},
+        ValidationErrors,
+    },
+};
+
+use common::dtos::photos::{
+    upload_fields, GpsPositionResponse, PhotoDerivativeResponse, PhotoFileResponse, PhotoListQuery,
+    PhotoMetadataResponse, PhotoRequest, PhotoResponse,
+};
+
+use axum::{
+    extract::{
+        multipart::{Field, MultipartRejection},
+        DefaultBodyLimit, Multipart, State,
+    },
+    http::StatusCode,
+    routing::{get, post},
+    Router,
+};
+use chrono::{DateTime, Utc};
+use std::{path::PathBuf, sync::Arc};
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+/// Url path under which the photo storage directory is served.
+pub const PHOTOS_URL_PATH: &str = "/media/photos";
+
+/// Bytes allowed on an upload request on top of the file, for the other multipart fields.
+const UPLOAD_FORM_OVERHEAD_BYTES: u64 = 64 * 1024;
+
+/// Title of an uploaded photo sent without title nor file name.
+const DEFAULT_UPLOAD_TITLE: &str = "Untitled";
+
+/// Photo routes, to be nested under `/api/photos`.
+///
+/// # Arguments
+///
+/// * `max_upload_bytes` - The maximum size of an uploaded photo file.
+pub fn router( max_upload_bytes: u64 ) -> Router<ApiState>
+{
+    let upload_body_limit =
+        usize::try_from( max_upload_bytes.saturating_add( UPLOAD_FORM_OVERHEAD_BYTES ) ).unwrap_or( usize::MAX );
+
+    Router::new()
+        .route( "/", get( list_photos ).post( create_photo ) )
+        .route( "/upload", post( upload_photo ).layer( DefaultBodyLimit::max( upload_body_limit ) ) )
+        .route( "/:id", get( get_photo ).put( update_photo ).delete( delete_photo ) )
+}
+
+impl From<Photo> for PhotoResponse
+{
+    fn from( photo: Photo ) -> Self
+    {
+        Self {
+            id:          photo.id,
+            album_id:    photo.album_id,
+            title:       photo.title,
+            description: photo.description,
+            position:    photo.position,
+            taken_at:    photo.taken_at,
+            file:        photo.file.map( |file| PhotoFileResponse {
+                url:           photo_file_url( photo.id, &file.file_name ),
+                mime_type:     file.mime_type,
+                size_bytes:    file.size_bytes,
+                original_name: file.original_name,
+                derivatives:   file
+                    .derivatives
+                    .into_iter()
+                    .map( |derivative| PhotoDerivativeResponse {
+                        url:        photo_file_url( photo.id, &derivative.file_name ),
+                        name:       derivative.name,
+                        mime_type:  derivative.mime_type,
+                        width:      derivative.width,
+                        height:     derivative.height,
+                        size_bytes: derivative.size_bytes,
+                    } )
+                    .collect(),
+                metadata:      file.metadata.into(),
+            } ),
+            created_at:  photo.created_at,
+            updated_at:  photo.updated_at,
+        }
+    }
+}
+
+impl From<PhotoMetadata> for PhotoMetadataResponse
+{
+    fn from( metadata: PhotoMetadata ) -> Self
+    {
+        Self {
+            camera_make:        metadata.camera_make,
+            camera_model:       metadata.camera_model,
+            lens_model:         metadata.lens_model,
+            focal_length_mm:    metadata.focal_length_mm,
+            f_number:           metadata.f_number,
+            exposure_time_secs: metadata.exposure_time_secs,
+            iso:                metadata.iso,
+            taken_at:           metadata.taken_at,
+            gps:                metadata.gps.map( |gps| GpsPositionResponse {
+                latitude:   gps.latitude,
+                longitude:  gps.longitude,
+                altitude_m: gps.altitude_m,
+            } ),
+            orientation:        metadata.orientation,
+            rating:             metadata.rating,
+        }
+    }
+}
+
+/// Url of a file of a photo, served from the photo storage directory.
+fn photo_file_url( photo_id: Uuid, file_name: &str ) -> String { format!( "{PHOTOS_URL_PATH}/{photo_id}/{file_name}" ) }
+
+/// Checks that the album of a photo exists.
+async fn validate_album_exists( repositories: &Repositories, album_id: Option<Uuid> ) -> Result<(), ApiError>
+{
+    if let Some( album_id ) = album_id
+    {
+        if repositories.albums.get( album_id ).await?.is_none()
+        {
+            let mut errors = ValidationErrors::default();
+            errors.add( "album_id", "must be an existing album" );
+
+            return Err( errors.into() );
+        }
+    }
+
+    Ok( () )
+}
+
+async fn list_photos(
+    State( repositories ): State<Repositories>,
+    Query( query ): Query<PhotoListQuery>,
+) -> Result<Json<Vec<PhotoResponse>>, ApiError>
+{
+    let photos = repositories.photos.list( query.album_id ).await?;
+
+    Ok( Json( photos.into_iter().map( PhotoResponse::from ).collect() ) )
+}
+
+async fn get_photo(
+    State( repositories ): State<Repositories>,
+    Path( id ): Path<Uuid>,
+) -> Result<Json<PhotoResponse>, ApiError>
+{
+    let photo = repositories.photos.get( id ).await?;
+
+    photo.map( |photo| Json( photo.into() ) ).ok_or( ApiError::NotFound )
+}
+
+async fn create_photo(
+    State( repositories ): State<Repositories>,
+    Json( request ): Json<PhotoRequest>,
+) -> Result<( StatusCode, Json<PhotoResponse> ), ApiError>
+{
+    validators::photos::validate_photo_request( &request )?;
+    validate_album_exists( &repositories, request.album_id ).await?;
+
+    let position = match request.position
+    {
+        Some( position ) => position,
+        None => repositories.photos.next_position( request.album_id ).await?,
+    };
+
+    let photo = Photo::new( PhotoFields {
+        album_id:    request.album_id,
+        title:       request.title,
+        description: request.description,
+        position,
+        taken_at:    request.taken_at,
+    } );
+
+    repositories.photos.insert( &photo ).await?;
+
+    Ok( ( StatusCode::CREATED, Json( photo.into() ) ) )
+}
+
+async fn update_photo(
+    State( repositories ): State<Repositories>,
+    Path( id ): Path<Uuid>,
+    Json( request ): Json<PhotoRequest>,
+) -> Result<Json<PhotoResponse>, ApiError>
+{
+    validators::photos::validate_photo_request( &request )?;
+    validate_album_exists( &repositories, request.album_id ).await?;
+
+    let mut photo = repositories.photos.get( id ).await?.ok_or( ApiError::NotFound )?;
+
+    // A photo moved out of its album can't stay as its cover.
+    if photo.album_id != request.album_id
+    {
+        repositories.albums.clear_cover( id ).await?;
+    }
+
+    photo.update( PhotoFields {
+        album_id:    request.album_id,
+        title:       request.title,
+        description: request.description,
+        position:    request.position.unwrap_or( photo.position ),
+        taken_at:    request.taken_at,
+    } );
+
+    if !repositories.photos.update( &photo ).await?
+    {
+        return Err( ApiError::NotFound );
+    }
+
+    Ok( Json( photo.into() ) )
+}
+
+async fn delete_photo(
+    State( repositories ): State<Repositories>,
+    State( photo_storage ): State<PhotoStorage>,
+    Path( id ): Path<Uuid>,
+) -> Result<StatusCode, ApiError>
+{
+    if !repositories.photos.delete( id ).await?
+    {
+        return Err( ApiError::NotFound );
+    }
+
+    repositories.albums.clear_cover( id ).await?;
+    photo_storage.remove( id ).await?;
+
+    Ok( StatusCode::NO_CONTENT )
+}
+
+/// Photo file received from an upload, still on its temporary path.
+struct ReceivedFile
+{
+    temp_path:     PathBuf,
+    mime_type:     ImageMimeType,
+    size_bytes:    u64,
+    original_name: Option<String>,
+}
+
+/// Text fields received from an upload.
+#[derive(Default)]
+struct ReceivedFields
+{
+    album_id:    Option<String>,
+    title:       Option<String>,
+    description: Option<String>,
+    position:    Option<String>,
+    taken_at:    Option<String>,
+}
+
+/// Creates a photo from a multipart form, see [`upload_fields`].
+///
+/// The file is streamed to a temporary file while its size and type are validated, and moved to the
+/// photo storage once the photo is validated.
+async fn upload_photo(
+    State( repositories ): State<Repositories>,
+    State( photo_storage ): State<PhotoStorage>,
+    State( upload_rules ): State<Arc<UploadRules>>,
+    multipart: Result<Multipart, MultipartRejection>,
+) -> Result<( StatusCode, Json<PhotoResponse> ), ApiError>
+{
+    let mut multipart = multipart?;
+    let mut received_file = None;
+    let mut received_fields = ReceivedFields::default();
+
+    let result = async {
+        while let Some( field ) = multipart.next_field().await?
+        {
+            let name = field.name().unwrap_or_default().to_owned();
+
+            if name == upload_fields::FILE
+            {
+                if received_file.is_some()
+                {
+                    let mut errors = ValidationErrors::default();
+                    errors.add( upload_fields::FILE, "must be sent only once" );
+
+                    return Err( errors.into() );
+                }
+
+                received_file = Some( receive_file( field, &photo_storage, &upload_rules ).await? );
+                continue;
+            }
+
+            let value = Some( field.text().await? );
+
+            match name.as_str()
+            {
+                upload_fields::ALBUM_ID => received_fields.album_id = value,
+                upload_fields::TITLE => received_fields.title = value,
+                upload_fields::DESCRIPTION => received_fields.description = value,
+                upload_fields::POSITION => received_fields.position = value,
+                upload_fields::TAKEN_AT => received_fields.taken_at = value,
+                _ => (),
+            }
+        }
+
+        if let Some( file ) = received_file.as_ref()
+        {
+            create_uploaded_photo( &repositories, &photo_storage, file, received_fields ).await
+        }
+        else
+        {
+            let mut errors = ValidationErrors::default();
+            errors.add( upload_fields::FILE, "is required" );
+
+            Err( errors.into() )
+        }
+    }
+    .await;
+
+    // Never leave a temporary file behind, it was already moved if the photo was created.
+    if let ( Err( _ ), Some( file ) ) = ( &result, &received_file )
+    {
+        let _ = tokio::fs::remove_file( &file.temp_path ).await;
+    }
+
+    result
+}
+
+/// Streams the file field to a temporary file, validating its size and type as it is received.
+async fn receive_file(
+    mut field: Field<'_>,
+    photo_storage: &PhotoStorage,
+    upload_rules: &UploadRules,
+) -> Result<ReceivedFile, ApiError>
+{
+    let original_name = field.file_name().map( ToOwned::to_owned );
+    let temp_path = photo_storage.new_temp_path().await?;
+
+    let result = async {
+        let mut temp_file = tokio::fs::File::create( &temp_path ).await.map_err( ApiError::internal )?;
+
+        let mut header = Vec::with_capacity( SNIFF_LEN );
+        let mut mime_type = None;
+        let mut size_bytes: u64 = 0;
+
+        while let Some( chunk ) = field.chunk().await?
+        {
+            size_bytes += chunk.len() as u64;
+            upload_rules.validate_size( upload_fields::FILE, size_bytes )?;
+
+            // Sniff the type as soon as enough bytes are received, to reject it early.
+            if mime_type.is_none()
+            {
+                header.extend( chunk.iter().take( SNIFF_LEN - header.len() ) );
+
+                if header.len() == SNIFF_LEN
+                {
+                    mime_type = Some( upload_rules.validate_mime_type( upload_fields::FILE, &header )? );
+                }
+            }
+
+            temp_file.write_all( &chunk ).await.map_err( ApiError::internal )?;
+        }
+
+        temp_file.flush().await.map_err( ApiError::internal )?;
+
+        // Files smaller than the sniffing length.
+        let mime_type = match mime_type
+        {
+            Some( mime_type ) => mime_type,
+            None => upload_rules.validate_mime_type( upload_fields::FILE, &header )?,
+        };
+
+        Ok::<_, ApiError>( ( mime_type, size_bytes ) )
+    }
+    .await;
+
+    match result
+    {
+        Ok( ( mime_type, size_bytes ) ) => Ok( ReceivedFile {
+            temp_path,
+            mime_type,
+            size_bytes,
+            original_name,
+        } ),
+        Err( err ) =>
+        {
+            let _ = tokio::fs::remove_file( &temp_path ).await;
+            Err( err )
+        }
+    }
+}
+
+/// Validates the uploaded fields and creates the photo with the received file.
+async fn create_uploaded_photo(
+    repositories: &Repositories,
+    photo_storage: &PhotoStorage,
+    file: &ReceivedFile,
+    fields: ReceivedFields,
+) -> Result<( StatusCode, Json<PhotoResponse> ), ApiError>
+{
+    let request = photo_request_from_fields( fields, file.original_name.as_deref() )?;
+
+    validators::photos::validate_photo_request( &request )?;
+    validate_album_exists( repositories, request.album_id ).await?;
+
+    let position = match request.position
+    {
+        Some( position ) => position,
+        None => repositories.photos.next_position( request.album_id ).await?,
+    };
+
+    let mut photo = Photo::new( PhotoFields {
+        album_id:    request.album_id,
+        title:       request.title,
+        description: request.description,
+        position,
+        taken_at:    request.taken_at,
+    } );
+
+    let file_name = PhotoStorage::original_file_name( file.mime_type.extension() );
+
+    let original_path = photo_storage.persist( &file.temp_path, photo.id, &file_name ).await?;
+
+    // The photo directory is publicly served, it must not outlive a photo that failed to be created.
+    let result = async {
+        let metadata = tokio::task::spawn_blocking( move || photo_metadata::extract_metadata( &original_path ) )
+            .await
+            .map_err( ApiError::internal )?;
+
+        // The date sent with the upload takes precedence over the one recorded by the camera.
+        photo.taken_at = photo.taken_at.or( metadata.taken_at );
+
+        // A photo is still usable from its original if the derivatives fail, e.g. for AVIF originals.
+        let derivatives = photo_storage
+            .generate_derivatives( photo.id, &file_name )
+            .await
+            .unwrap_or_else( |err| {
+                tracing::warn!( "Failed to generate the derivatives of photo {}: {:?}", photo.id, err );
+                Vec::new()
+            } );
+
+        photo.file = Some( PhotoFile {
+            file_name,
+            mime_type: file.mime_type.mime_type().to_owned(),
+            size_bytes: file.size_bytes,
+            original_name: file.original_name.clone(),
+            derivatives,
+            metadata,
+        } );
+
+        repositories.photos.insert( &photo ).await?;
+
+        Ok::<_, ApiError>( () )
+    }
+    .await;
+
+    if let Err( err ) = result
+    {
+        let _ = photo_storage.remove( photo.id ).await;
+        return Err( err );
+    }
+
+    Ok( ( StatusCode::CREATED, Json( photo.into() ) ) )
+}
+
+/// Parses the uploaded text fields into a photo request. The title defaults to the file name
+/// without extension, or [`DEFAULT_UPLOAD_TITLE`] if there is none.
+fn photo_request_from_fields(
+    fields: ReceivedFields,
+    original_name: Option<&str>,
+) -> Result<PhotoRequest, ValidationErrors>
+{
+    let mut errors = ValidationErrors::default();
+
+    let album_id = fields.album_id.filter( |album_id| !album_id.is_empty() ).and_then( |album_id| {
+        Uuid::parse_str( &album_id )
+            .map_err( |_| errors.add( upload_fields::ALBUM_ID, "must be a valid id" ) )
+            .ok()
+    } );
+
+    let position = fields.position.filter( |position| !position.is_empty() ).and_then( |position| {
+        position
+            .parse::<u32>()
+            .map_err( |_| errors.add( upload_fields::POSITION, "must be a positive integer" ) )
+            .ok()
+    } );
+
+    let taken_at = fields.taken_at.filter( |taken_at| !taken_at.is_empty() ).and_then( |taken_at| {
+        DateTime::parse_from_rfc3339( &taken_at )
+            .map( |taken_at| taken_at.with_timezone( &Utc ) )
+            .map_err( |_| errors.add( upload_fields::TAKEN_AT, "must be an RFC 3339 date" ) )
+            .ok()
+    } );
+
+    let title = fields.title.filter( |title| !title.is_empty() ).unwrap_or_else( || {
+        original_name
+            .map( |name| name.rsplit_once( '.' ).map_or( name, |( stem, _ )| stem ) )
+            .filter( |stem| !stem.is_empty() )
+            .unwrap_or( DEFAULT_UPLOAD_TITLE )
+            .to_owned()
+    } );
+
+    errors.into_result()?;
+
+    Ok( PhotoRequest {
+        album_id,
+        title,
+        description: fields.description.filter( |description| !description.is_empty() ),
+        position,
+        taken_at,
+    } )
+}
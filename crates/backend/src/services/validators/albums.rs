@@ -0,0 +1,45 @@
+use super::{validate_description, validate_title, ValidationErrors};
+
+use common::dtos::albums::AlbumRequest;
+
+/// Validates the body of an album create or update request.
+///
+/// # Errors
+///
+/// Returns the errors of the fields that failed validation.
+pub fn validate_album_request( request: &AlbumRequest ) -> Result<(), ValidationErrors>
+{
+    let mut errors = ValidationErrors::default();
+
+    validate_title( &mut errors, "title", &request.title );
+    validate_description( &mut errors, "description", request.description.as_deref() );
+
+    errors.into_result()
+}
+
+#[cfg( test )]
+#[allow( non_snake_case )]
+mod tests
+{
+    use super::*;
+    use crate::services::validators::DESCRIPTION_MAX_CHARS;
+
+    #[test]
+    fn validate_album_request__invalid_fields__all_reported()
+    {
+        let request = AlbumRequest {
+            title:          String::new(),
+            description:    Some( "a".repeat( DESCRIPTION_MAX_CHARS + 1 ) ),
+            position:       None,
+            cover_photo_id: None,
+        };
+        let fields: Vec<String> = validate_album_request( &request )
+            .unwrap_err()
+            .into_inner()
+            .into_iter()
+            .map( |error| error.field )
+            .collect();
+
+        assert_eq!( fields, ["title", "description"] );
+    }
+}
@@ -1 +1,119 @@
+// Modules.
+pub mod albums;
+pub mod photos;
+pub mod uploads;
 
+use common::dtos::errors::FieldError;
+
+/// Maximum number of characters of a title.
+pub const TITLE_MAX_CHARS: usize = 200;
+
+/// Maximum number of characters of a description.
+pub const DESCRIPTION_MAX_CHARS: usize = 5000;
+
+/// Errors of the fields that failed validation, answered with `422 Unprocessable Entity` as an
+/// [`ApiError::Validation`](crate::services::errors::ApiError::Validation).
+#[derive(Debug, Default)]
+pub struct ValidationErrors( Vec<FieldError> );
+
+impl ValidationErrors
+{
+    pub fn add( &mut self, field: &str, message: &str )
+    {
+        self.0.push( FieldError {
+            field:   field.to_owned(),
+            message: message.to_owned(),
+        } );
+    }
+
+    #[must_use]
+    pub const fn is_empty( &self ) -> bool { self.0.is_empty() }
+
+    #[must_use]
+    pub fn into_inner( self ) -> Vec<FieldError> { self.0 }
+
+    /// Returns `Ok` if there are no errors.
+    ///
+    /// # Errors
+    ///
+    /// Returns itself if there are errors.
+    pub fn into_result( self ) -> Result<(), Self>
+    {
+        if self.is_empty()
+        {
+            Ok( () )
+        }
+        else
+        {
+            Err( self )
+        }
+    }
+}
+
+/// Validates a required title: not blank and at most [`TITLE_MAX_CHARS`] characters.
+pub fn validate_title( errors: &mut ValidationErrors, field: &str, title: &str )
+{
+    if title.trim().is_empty()
+    {
+        errors.add( field, "must not be blank" );
+    }
+    else if title.chars().count() > TITLE_MAX_CHARS
+    {
+        errors.add( field, &format!( "must have at most {TITLE_MAX_CHARS} characters" ) );
+    }
+}
+
+/// Validates an optional description: at most [`DESCRIPTION_MAX_CHARS`] characters.
+pub fn validate_description( errors: &mut ValidationErrors, field: &str, description: Option<&str> )
+{
+    if description.is_some_and( |description| description.chars().count() > DESCRIPTION_MAX_CHARS )
+    {
+        errors.add( field, &format!( "must have at most {DESCRIPTION_MAX_CHARS} characters" ) );
+    }
+}
+
+#[cfg( test )]
+#[allow( non_snake_case )]
+mod tests
+{
+    use super::*;
+
+    fn title_errors( title: &str ) -> Vec<FieldError>
+    {
+        let mut errors = ValidationErrors::default();
+        validate_title( &mut errors, "title", title );
+
+        errors.into_inner()
+    }
+
+    #[test]
+    fn validate_title__blank__error()
+    {
+        assert_eq!( title_errors( " \t" ), vec![FieldError {
+            field:   "title".to_owned(),
+            message: "must not be blank".to_owned(),
+        }] );
+    }
+
+    #[test]
+    fn validate_title__max_chars__counted_in_characters()
+    {
+        assert!( title_errors( &"é".repeat( TITLE_MAX_CHARS ) ).is_empty() );
+        assert_eq!( title_errors( &"é".repeat( TITLE_MAX_CHARS + 1 ) ), vec![FieldError {
+            field:   "title".to_owned(),
+            message: format!( "must have at most {TITLE_MAX_CHARS} characters" ),
+        }] );
+    }
+
+    #[test]
+    fn validate_description__none_or_too_long__error_only_when_too_long()
+    {
+        let mut errors = ValidationErrors::default();
+        validate_description( &mut errors, "description", None );
+        validate_description( &mut errors, "description", Some( &"a".repeat( DESCRIPTION_MAX_CHARS ) ) );
+        assert!( errors.is_empty() );
+
+        validate_description( &mut errors, "description", Some( &"a".repeat( DESCRIPTION_MAX_CHARS + 1 ) ) );
+        assert_eq!( errors.into_result().unwrap_err().into_inner()[0].field, "description" );
+    }
+}
@@ -0,0 +1,55 @@
+use super::{validate_description, validate_title, ValidationErrors};
+
+use common::dtos::photos::PhotoRequest;
+
+use chrono::Utc;
+
+/// Validates the body of a photo create or update request.
+///
+/// # Errors
+///
+/// Returns the errors of the fields that failed validation.
+pub fn validate_photo_request( request: &PhotoRequest ) -> Result<(), ValidationErrors>
+{
+    let mut errors = ValidationErrors::default();
+
+    validate_title( &mut errors, "title", &request.title );
+    validate_description( &mut errors, "description", request.description.as_deref() );
+
+    if request.taken_at.is_some_and( |taken_at| taken_at > Utc::now() )
+    {
+        errors.add( "taken_at", "must not be in the future" );
+    }
+
+    errors.into_result()
+}
+
+#[cfg( test )]
+#[allow( non_snake_case )]
+mod tests
+{
+    use super::*;
+
+    use chrono::Duration;
+
+    fn request( taken_at: chrono::DateTime<Utc> ) -> PhotoRequest
+    {
+        PhotoRequest {
+            album_id:    None,
+            title:       "Sunset".to_owned(),
+            description: None,
+            position:    None,
+            taken_at:    Some( taken_at ),
+        }
+    }
+
+    #[test]
+    fn validate_photo_request__taken_at__not_in_the_future()
+    {
+        assert!( validate_photo_request( &request( Utc::now() - Duration::days( 1 ) ) ).is_ok() );
+
+        let errors = validate_photo_request( &request( Utc::now() + Duration::days( 1 ) ) ).unwrap_err().into_inner();
+        assert_eq!( errors.len(), 1 );
+        assert_eq!( errors[0].field, "taken_at" );
+    }
+}
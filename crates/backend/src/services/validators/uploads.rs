@@ -0,0 +1,188 @@
+use super::ValidationErrors;
+
+/// Number of bytes needed to sniff the type of an image.
+pub const SNIFF_LEN: usize = 16;
+
+/// Image types that can be detected from their magic bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageMimeType
+{
+    Jpeg,
+    Png,
+    Webp,
+    Avif,
+}
+
+impl ImageMimeType
+{
+    /// Detects the image type from the first bytes of a file, ignoring its name and declared type.
+    #[must_use]
+    pub fn sniff( header: &[u8] ) -> Option<Self>
+    {
+        match header
+        {
+            [0xFF, 0xD8, 0xFF, ..] => Some( Self::Jpeg ),
+            [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => Some( Self::Png ),
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some( Self::Webp ),
+            [_, _, _, _, b'f', b't', b'y', b'p', b'a', b'v', b'i', b'f' | b's', ..] => Some( Self::Avif ),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn mime_type( self ) -> &'static str
+    {
+        match self
+        {
+            Self::Jpeg => "image/jpeg",
+            Self::Png => "image/png",
+            Self::Webp => "image/webp",
+            Self::Avif => "image/avif",
+        }
+    }
+
+    #[must_use]
+    pub const fn extension( self ) -> &'static str
+    {
+        match self
+        {
+            Self::Jpeg => "jpg",
+            Self::Png => "png",
+            Self::Webp => "webp",
+            Self::Avif => "avif",
+        }
+    }
+}
+
+/// Rules that an uploaded photo file must follow.
+#[derive(Debug, Clone)]
+pub struct UploadRules
+{
+    pub max_bytes:          u64,
+    pub allowed_mime_types: Vec<String>,
+}
+
+impl UploadRules
+{
+    /// Validates the size of an upload, can be called while it is received.
+    ///
+    /// # Errors
+    ///
+    /// If the size is above the maximum.
+    pub fn validate_size( &self, field: &str, size: u64 ) -> Result<(), ValidationErrors>
+    {
+        let mut errors = ValidationErrors::default();
+
+        if size > self.max_bytes
+        {
+            errors.add( field, &format!( "must have at most {} bytes", self.max_bytes ) );
+        }
+
+        errors.into_result()
+    }
+
+    /// Validates the type of an upload from its first bytes ( at least [`SNIFF_LEN`] if available ).
+    ///
+    /// # Errors
+    ///
+    /// If the type can't be detected or is not allowed.
+    pub fn validate_mime_type( &self, field: &str, header: &[u8] ) -> Result<ImageMimeType, ValidationErrors>
+    {
+        let mut errors = ValidationErrors::default();
+
+        match ImageMimeType::sniff( header )
+        {
+            Some( mime_type ) if self.allowed_mime_types.iter().any( |allowed| allowed == mime_type.mime_type() ) =>
+            {
+                return Ok( mime_type );
+            }
+            Some( mime_type ) => errors.add( field, &format!( "type {} is not allowed", mime_type.mime_type() ) ),
+            None => errors.add( field, "must be an image of a supported type" ),
+        }
+
+        Err( errors )
+    }
+}
+
+#[cfg( test )]
+#[allow( non_snake_case )]
+mod tests
+{
+    use super::*;
+
+    const JPEG: &[u8] = b"\xFF\xD8\xFF\xE0\x00\x10JFIF\x00\x01\x01\x00\x00\x01";
+    const PNG: &[u8] = b"\x89PNG\r\n\x1A\n\x00\x00\x00\x0DIHDR";
+    const WEBP: &[u8] = b"RIFF\x24\x00\x00\x00WEBPVP8 ";
+    const AVIF: &[u8] = b"\x00\x00\x00\x1CftypavifMA1B";
+    const AVIF_SEQUENCE: &[u8] = b"\x00\x00\x00\x1CftypavisMA1B";
+
+    fn rules() -> UploadRules
+    {
+        UploadRules {
+            max_bytes:          1024,
+            allowed_mime_types: vec!["image/jpeg".to_owned(), "image/png".to_owned()],
+        }
+    }
+
+    fn messages( errors: ValidationErrors ) -> Vec<String>
+    {
+        errors.into_inner().into_iter().map( |error| error.message ).collect()
+    }
+
+    #[test]
+    fn sniff__supported_signatures__detected()
+    {
+        assert_eq!( ImageMimeType::sniff( JPEG ), Some( ImageMimeType::Jpeg ) );
+        assert_eq!( ImageMimeType::sniff( PNG ), Some( ImageMimeType::Png ) );
+        assert_eq!( ImageMimeType::sniff( WEBP ), Some( ImageMimeType::Webp ) );
+        assert_eq!( ImageMimeType::sniff( AVIF ), Some( ImageMimeType::Avif ) );
+        assert_eq!( ImageMimeType::sniff( AVIF_SEQUENCE ), Some( ImageMimeType::Avif ) );
+    }
+
+    #[test]
+    fn sniff__truncated_headers__not_detected()
+    {
+        assert_eq!( ImageMimeType::sniff( &[] ), None );
+
+        for ( signature, signature_len ) in [( JPEG, 3 ), ( PNG, 8 ), ( WEBP, 12 ), ( AVIF, 12 )]
+        {
+            assert_eq!( ImageMimeType::sniff( &signature[..signature_len - 1] ), None );
+            assert!( ImageMimeType::sniff( &signature[..signature_len] ).is_some() );
+        }
+    }
+
+    #[test]
+    fn sniff__other_types__not_detected()
+    {
+        assert_eq!( ImageMimeType::sniff( b"GIF89a\x01\x00\x01\x00\x00\x00\x00\x00\x00\x00" ), None );
+        assert_eq!( ImageMimeType::sniff( b"RIFF\x24\x00\x00\x00WAVEfmt " ), None );
+        assert_eq!( ImageMimeType::sniff( b"\x00\x00\x00\x18ftypmp42\x00\x00\x00\x00" ), None );
+    }
+
+    #[test]
+    fn validate_mime_type__spoofed_extension__rejected_from_content()
+    {
+        // E.g. a script uploaded as `photo.jpg` with an `image/jpeg` part type, only its bytes are looked at.
+        let errors = rules().validate_mime_type( "file", b"<?php system($_GET['c']); ?>" ).unwrap_err();
+
+        assert_eq!( messages( errors ), ["must be an image of a supported type"] );
+    }
+
+    #[test]
+    fn validate_mime_type__detected_type__allowed_only_if_configured()
+    {
+        assert_eq!( rules().validate_mime_type( "file", PNG ).unwrap(), ImageMimeType::Png );
+
+        let errors = rules().validate_mime_type( "file", WEBP ).unwrap_err();
+        assert_eq!( messages( errors ), ["type image/webp is not allowed"] );
+    }
+
+    #[test]
+    fn validate_size__above_max__rejected()
+    {
+        assert!( rules().validate_size( "file", 1024 ).is_ok() );
+
+        let errors = rules().validate_size( "file", 1025 ).unwrap_err();
+        assert_eq!( messages( errors ), ["must have at most 1024 bytes"] );
+    }
+}
@@ -1,25 +1,47 @@
 #![allow( unused )]
 
+use crate::{
+    services::features::infrastructure::images::{DerivativeFormat, DerivativeSize},
+    tls::TlsVersion,
+};
+
 use settings::{ImportFigment, RuntimeEnvironmentType};
 
 use derive_getters::Getters;
-use lazy_static::lazy_static;
 use serde::Deserialize;
+use std::sync::LazyLock;
 
-lazy_static! {
-    pub static ref GENERAL: GeneralConfigs =
-        GeneralConfigs::import( "./configs/backend/general.toml", "backend_general_", None );
-    pub static ref SERVER: ServerConfigs = ServerConfigs::import(
+pub static GENERAL: LazyLock<GeneralConfigs> = LazyLock::new( || {
+    GeneralConfigs::import( "./configs/backend/general.toml", "backend_general_", None )
+} );
+pub static SERVER: LazyLock<ServerConfigs> = LazyLock::new( || {
+    ServerConfigs::import(
         "./configs/backend/server.toml",
         "backend_server_",
         Some( GENERAL.run_env() )
-    );
-    pub static ref LOGGER: LoggerConfigs = LoggerConfigs::import(
+    )
+} );
+pub static LOGGER: LazyLock<LoggerConfigs> = LazyLock::new( || {
+    LoggerConfigs::import(
         "./configs/backend/logger.toml",
         "backend_logger_",
         Some( GENERAL.run_env() )
-    );
-}
+    )
+} );
+pub static DATABASE: LazyLock<DatabaseConfigs> = LazyLock::new( || {
+    DatabaseConfigs::import(
+        "./configs/backend/database.toml",
+        "backend_database_",
+        Some( GENERAL.run_env() )
+    )
+} );
+pub static STORAGE: LazyLock<StorageConfigs> = LazyLock::new( || {
+    StorageConfigs::import(
+        "./configs/backend/storage.toml",
+        "backend_storage_",
+        Some( GENERAL.run_env() )
+    )
+} );
 
 #[derive(Debug, Deserialize, Getters)]
 pub struct GeneralConfigs
@@ -32,10 +54,24 @@ pub struct GeneralConfigs
 #[derive(Debug, Deserialize, Getters)]
 pub struct ServerConfigs
 {
-    addr:       String,
-    port:       u16,
-    static_dir: String,
-    assets_dir: String,
+    addr:                  String,
+    port:                  u16,
+    static_dir:            String,
+    assets_dir:            String,
+    metrics_port:          Option<u16>,
+    shutdown_timeout_secs: u64,
+    tls:                   Option<TlsConfigs>,
+}
+
+#[derive(Debug, Deserialize, Getters)]
+pub struct TlsConfigs
+{
+    cert_path:            String,
+    key_path:             String,
+    min_version:          TlsVersion,
+    alpn_protocols:       Vec<String>,
+    reload_interval_secs: u64,
+    redirect_port:        Option<u16>,
 }
 
 #[derive(Debug, Deserialize, Getters)]
@@ -48,6 +84,28 @@ pub struct LoggerConfigs
     files_prefix:      Option<String>,
 }
 
+#[derive(Debug, Deserialize, Getters)]
+pub struct DatabaseConfigs
+{
+    url:                  String,
+    max_connections:      u32,
+    acquire_timeout_secs: u64,
+    run_migrations:       bool,
+}
+
+#[derive(Debug, Deserialize, Getters)]
+pub struct StorageConfigs
+{
+    photos_dir:         String,
+    max_upload_bytes:   u64,
+    allowed_mime_types: Vec<String>,
+    derivative_sizes:   Vec<DerivativeSize>,
+    derivative_formats: Vec<DerivativeFormat>,
+    derivative_quality: u8,
+}
+
 impl ImportFigment<Self> for GeneralConfigs {}
 impl ImportFigment<Self> for ServerConfigs {}
 impl ImportFigment<Self> for LoggerConfigs {}
+impl ImportFigment<Self> for DatabaseConfigs {}
+impl ImportFigment<Self> for StorageConfigs {}
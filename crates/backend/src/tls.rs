@@ -0,0 +1,371 @@
+//! TLS termination with rustls: certificates reloaded when their files change, a hyper acceptor of TLS
+//! connections, and the plain-HTTP router redirecting to HTTPS.
+
+use crate::{services::errors::ApiError, Result};
+
+use axum::{
+    http::{self, uri::Authority, Request},
+    response::{IntoResponse, Redirect, Response},
+    Router,
+};
+use color_eyre::eyre::eyre;
+use hyper::{server::accept::Accept, Body};
+use rustls::{
+    server::{ClientHello, ResolvesServerCert},
+    sign::{self, CertifiedKey},
+    version, Certificate, PrivateKey, ServerConfig, SupportedProtocolVersion,
+};
+use rustls_pemfile::Item;
+use serde::Deserialize;
+use std::{
+    io::BufReader,
+    net::TcpListener,
+    path::{Path, PathBuf},
+    pin::Pin,
+    str::FromStr,
+    sync::{Arc, Mutex, RwLock},
+    task::{Context, Poll},
+    time::{Duration, SystemTime},
+};
+use tokio::{net::TcpStream, sync::mpsc, task::JoinHandle};
+use tokio_rustls::{server::TlsStream, TlsAcceptor};
+
+/// Maximum duration of a TLS handshake, so that idle connections don't hold an accept slot.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs( 10 );
+
+/// Delay before accepting connections again after an error, e.g. when the process is out of file descriptors.
+const ACCEPT_ERROR_DELAY: Duration = Duration::from_secs( 1 );
+
+/// Minimum TLS version accepted.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TlsVersion
+{
+    #[serde( rename = "1.2" )]
+    Tls12,
+    #[serde( rename = "1.3" )]
+    Tls13,
+}
+
+static TLS13_VERSIONS: [&SupportedProtocolVersion; 1] = [&version::TLS13];
+
+impl TlsVersion
+{
+    fn protocol_versions( self ) -> &'static [&'static SupportedProtocolVersion]
+    {
+        match self
+        {
+            Self::Tls12 => rustls::ALL_VERSIONS,
+            Self::Tls13 => &TLS13_VERSIONS,
+        }
+    }
+}
+
+/// Certificate chain and private key read from PEM files, reloaded by [`CertificateResolver::reload_if_changed`] when
+/// the files are modified, e.g. when renewed by certbot.
+pub struct CertificateResolver
+{
+    cert_path:     PathBuf,
+    key_path:      PathBuf,
+    certified_key: RwLock<Arc<CertifiedKey>>,
+    modified:      Mutex<Option<SystemTime>>,
+}
+
+impl CertificateResolver
+{
+    /// Reads the certificate chain and the private key.
+    ///
+    /// # Errors
+    ///
+    /// If the files can't be read, or don't hold a certificate and a supported private key.
+    pub fn new( cert_path: &str, key_path: &str ) -> Result<Self>
+    {
+        let cert_path = PathBuf::from( cert_path );
+        let key_path = PathBuf::from( key_path );
+        let modified = last_modified( &cert_path, &key_path );
+        let certified_key = load_certified_key( &cert_path, &key_path )?;
+
+        Ok( Self {
+            cert_path,
+            key_path,
+            certified_key: RwLock::new( Arc::new( certified_key ) ),
+            modified: Mutex::new( modified ),
+        } )
+    }
+
+    /// Reloads the certificate chain and the private key if one of their files changed since the last load. Returns
+    /// whether they were reloaded.
+    ///
+    /// # Errors
+    ///
+    /// If the changed files are invalid, e.g. while they are being written. The previous certificate is kept and the
+    /// reload is tried again on the next call.
+    pub fn reload_if_changed( &self ) -> Result<bool>
+    {
+        let modified = last_modified( &self.cert_path, &self.key_path );
+
+        if self.modified.lock().map_or( true, |last_modified| *last_modified == modified )
+        {
+            return Ok( false );
+        }
+
+        let certified_key = load_certified_key( &self.cert_path, &self.key_path )?;
+
+        if let ( Ok( mut current ), Ok( mut last_modified ) ) = ( self.certified_key.write(), self.modified.lock() )
+        {
+            *current = Arc::new( certified_key );
+            *last_modified = modified;
+        }
+
+        Ok( true )
+    }
+
+    /// Checks the files at an interval and reloads them when they change, until the returned task is aborted.
+    #[must_use]
+    pub fn watch( self: &Arc<Self>, interval: Duration ) -> JoinHandle<()>
+    {
+        let resolver = self.clone();
+
+        tokio::spawn( async move {
+            let mut interval = tokio::time::interval( interval );
+
+            loop
+            {
+                interval.tick().await;
+
+                match resolver.reload_if_changed()
+                {
+                    Ok( true ) => tracing::info!( "Reloaded the TLS certificate {:?}.", resolver.cert_path ),
+                    Ok( false ) => (),
+                    Err( err ) => tracing::warn!( "Failed to reload the TLS certificate: {:?}", err ),
+                }
+            }
+        } )
+    }
+}
+
+impl ResolvesServerCert for CertificateResolver
+{
+    fn resolve( &self, _client_hello: ClientHello ) -> Option<Arc<CertifiedKey>>
+    {
+        self.certified_key.read().ok().map( |certified_key| certified_key.clone() )
+    }
+}
+
+/// Last modification time of the certificate and key files, `None` if one can't be read.
+fn last_modified( cert_path: &Path, key_path: &Path ) -> Option<SystemTime>
+{
+    let modified = |path: &Path| std::fs::metadata( path ).and_then( |metadata| metadata.modified() ).ok();
+
+    Some( modified( cert_path )?.max( modified( key_path )? ) )
+}
+
+fn load_certified_key( cert_path: &Path, key_path: &Path ) -> Result<CertifiedKey>
+{
+    let certs = rustls_pemfile::certs( &mut BufReader::new( std::fs::File::open( cert_path )? ) )?;
+
+    if certs.is_empty()
+    {
+        return Err( eyre!( "No certificate in {:?}", cert_path ) );
+    }
+
+    let key = rustls_pemfile::read_all( &mut BufReader::new( std::fs::File::open( key_path )? ) )?
+        .into_iter()
+        .find_map( |item| match item
+        {
+            Item::PKCS8Key( key ) | Item::RSAKey( key ) | Item::ECKey( key ) => Some( PrivateKey( key ) ),
+            _ => None,
+        } )
+        .ok_or_else( || eyre!( "No private key in {:?}", key_path ) )?;
+
+    let signing_key =
+        sign::any_supported_type( &key ).map_err( |_| eyre!( "Unsupported private key in {:?}", key_path ) )?;
+
+    Ok( CertifiedKey::new( certs.into_iter().map( Certificate ).collect(), signing_key ) )
+}
+
+/// Server configuration accepting the given minimum version and negotiating the given ALPN protocols, e.g. `h2` and
+/// `http/1.1`.
+///
+/// # Errors
+///
+/// If rustls doesn't support the versions.
+pub fn server_config(
+    resolver: Arc<CertificateResolver>,
+    min_version: TlsVersion,
+    alpn_protocols: &[String],
+) -> Result<ServerConfig>
+{
+    let mut config = ServerConfig::builder()
+        .with_safe_default_cipher_suites()
+        .with_safe_default_kx_groups()
+        .with_protocol_versions( min_version.protocol_versions() )?
+        .with_no_client_auth()
+        .with_cert_resolver( resolver );
+
+    config.alpn_protocols = alpn_protocols
+        .iter()
+        .map( |protocol| protocol.as_bytes().to_vec() )
+        .collect();
+
+    Ok( config )
+}
+
+/// Hyper acceptor of the TLS connections of a listener. Handshakes run concurrently, and connections failing them
+/// are dropped without stopping the server.
+pub struct TlsIncoming
+{
+    connections: mpsc::Receiver<TlsStream<TcpStream>>,
+    accept_task: JoinHandle<()>,
+}
+
+impl TlsIncoming
+{
+    /// Accepts the connections of a bound listener.
+    ///
+    /// # Errors
+    ///
+    /// If the listener can't be used by tokio.
+    pub fn new( listener: TcpListener, config: Arc<ServerConfig> ) -> Result<Self>
+    {
+        listener.set_nonblocking( true )?;
+
+        let listener = tokio::net::TcpListener::from_std( listener )?;
+        let acceptor = TlsAcceptor::from( config );
+        let ( connections_tx, connections ) = mpsc::channel( 64 );
+
+        let accept_task = tokio::spawn( async move {
+            loop
+            {
+                let stream = match listener.accept().await
+                {
+                    Ok( ( stream, _ ) ) => stream,
+                    Err( err ) =>
+                    {
+                        tracing::warn!( "Failed to accept a connection: {:?}", err );
+                        tokio::time::sleep( ACCEPT_ERROR_DELAY ).await;
+                        continue;
+                    }
+                };
+
+                let acceptor = acceptor.clone();
+                let connections_tx = connections_tx.clone();
+
+                tokio::spawn( async move {
+                    match tokio::time::timeout( HANDSHAKE_TIMEOUT, acceptor.accept( stream ) ).await
+                    {
+                        Ok( Ok( stream ) ) =>
+                        {
+                            let _ = connections_tx.send( stream ).await;
+                        }
+                        Ok( Err( err ) ) => tracing::debug!( "TLS handshake failed: {:?}", err ),
+                        Err( _ ) => tracing::debug!( "TLS handshake timed out." ),
+                    }
+                } );
+            }
+        } );
+
+        Ok( Self {
+            connections,
+            accept_task,
+        } )
+    }
+}
+
+impl Accept for TlsIncoming
+{
+    type Conn = TlsStream<TcpStream>;
+    type Error = std::io::Error;
+
+    fn poll_accept( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll<Option<Result<Self::Conn, Self::Error>>>
+    {
+        self.connections.poll_recv( cx ).map( |connection| connection.map( Ok ) )
+    }
+}
+
+impl Drop for TlsIncoming
+{
+    fn drop( &mut self ) { self.accept_task.abort(); }
+}
+
+/// Router redirecting every request to the same url with HTTPS on a port.
+pub fn redirect_router( https_port: u16 ) -> Router
+{
+    Router::new().fallback( move |req: Request<Body>| async move { redirect_to_https( &req, https_port ) } )
+}
+
+fn redirect_to_https( req: &Request<Body>, https_port: u16 ) -> Response
+{
+    let host = req
+        .headers()
+        .get( http::header::HOST )
+        .and_then( |host| host.to_str().ok() )
+        .or_else( || req.uri().host() );
+    let path_and_query = req.uri().path_and_query().map_or( "/", |path_and_query| path_and_query.as_str() );
+
+    host.and_then( |host| https_url( host, https_port, path_and_query ) ).map_or_else(
+        || ApiError::bad_request( "Missing or invalid host" ).into_response(),
+        |url| Redirect::permanent( &url ).into_response(),
+    )
+}
+
+/// Url with HTTPS of a host, on a port, omitted if the default one.
+fn https_url( host: &str, https_port: u16, path_and_query: &str ) -> Option<String>
+{
+    let host = Authority::from_str( host ).ok()?;
+
+    if https_port == 443
+    {
+        Some( format!( "https://{}{}", host.host(), path_and_query ) )
+    }
+    else
+    {
+        Some( format!( "https://{}:{}{}", host.host(), https_port, path_and_query ) )
+    }
+}
+
+#[cfg( test )]
+#[allow( non_snake_case )]
+mod tests
+{
+    use super::*;
+
+    fn fixture( name: &str ) -> PathBuf
+    {
+        Path::new( env!( "CARGO_MANIFEST_DIR" ) ).join( "tests/fixtures/tls" ).join( name )
+    }
+
+    #[test]
+    fn https_url__host_with_port__https_port()
+    {
+        assert_eq!(
+            https_url( "example.com:80", 443, "/albums?page=2" ).as_deref(),
+            Some( "https://example.com/albums?page=2" )
+        );
+        assert_eq!( https_url( "example.com", 8443, "/" ).as_deref(), Some( "https://example.com:8443/" ) );
+        assert_eq!( https_url( "[::1]:8080", 8443, "/" ).as_deref(), Some( "https://[::1]:8443/" ) );
+        assert_eq!( https_url( "bad host", 443, "/" ), None );
+    }
+
+    #[test]
+    fn reload_if_changed__replaced_files__new_certificate()
+    {
+        let dir = std::env::temp_dir().join( format!( "backend-tls-{}", uuid::Uuid::new_v4() ) );
+        std::fs::create_dir_all( &dir ).unwrap();
+        let ( cert_path, key_path ) = ( dir.join( "cert.pem" ), dir.join( "key.pem" ) );
+        std::fs::copy( fixture( "cert.pem" ), &cert_path ).unwrap();
+        std::fs::copy( fixture( "cert.key" ), &key_path ).unwrap();
+
+        let resolver = CertificateResolver::new( cert_path.to_str().unwrap(), key_path.to_str().unwrap() ).unwrap();
+        let first_cert = resolver.certified_key.read().unwrap().cert.clone();
+        assert!( !resolver.reload_if_changed().unwrap() );
+
+        std::thread::sleep( Duration::from_millis( 10 ) );
+        std::fs::copy( fixture( "renewed.pem" ), &cert_path ).unwrap();
+        std::fs::copy( fixture( "renewed.key" ), &key_path ).unwrap();
+
+        assert!( resolver.reload_if_changed().unwrap() );
+        assert_ne!( resolver.certified_key.read().unwrap().cert, first_cert );
+
+        std::fs::remove_dir_all( &dir ).unwrap();
+    }
+}
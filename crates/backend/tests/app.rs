@@ -0,0 +1,641 @@
+#![allow( non_snake_case )]
+
+use backend::{
+    app::{App, AppBuilder},
+    tls::{self, CertificateResolver, TlsVersion},
+    services::{
+        features::infrastructure::{by_features::Repositories, images::DerivativeOptions, storage::PhotoStorage},
+        routes::ApiState,
+        validators::uploads::UploadRules,
+    },
+};
+
+use common::dtos::{
+    albums::AlbumResponse,
+    errors::{ErrorCode, Problem, PROBLEM_JSON_CONTENT_TYPE},
+    photos::PhotoResponse,
+};
+use monitoring::logger::REQUEST_ID_HEADER;
+
+use axum::{routing::get, Router};
+use hyper::{body, client, header, Body, Client, HeaderMap, Method, Request, StatusCode};
+use image::{DynamicImage, ImageOutputFormat};
+use rustls::{Certificate, ClientConfig, RootCertStore, ServerConfig, ServerName};
+use serde::de::DeserializeOwned;
+use std::{
+    future::{self, Future},
+    io::Cursor,
+    net::{SocketAddr, TcpListener},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{net::TcpStream, sync::oneshot, task::JoinHandle};
+use tokio_rustls::TlsConnector;
+use uuid::Uuid;
+
+const INDEX_HTML: &str = "<!DOCTYPE html><html><head><title>Index</title></head><body><script \
+                          src=\"/static/app.js\"></script></body></html>";
+
+const MULTIPART_BOUNDARY: &str = "photo-story-boundary";
+
+/// Maximum size of the uploaded files of the test server.
+const MAX_UPLOAD_BYTES: u64 = 1024;
+
+/// App served on an ephemeral port, with its directories on a temporary directory removed on drop.
+struct TestServer
+{
+    addr: SocketAddr,
+    dir:  PathBuf,
+}
+
+impl TestServer
+{
+    async fn start() -> Self { Self::start_with( |builder| builder, None, future::pending(), Duration::ZERO ).await.0 }
+
+    /// Starts the app configured by a function, with TLS if configured, until the shutdown signal completes.
+    async fn start_with(
+        configure: impl FnOnce( AppBuilder ) -> AppBuilder,
+        tls_config: Option<Arc<ServerConfig>>,
+        shutdown_signal: impl Future<Output = ()> + Send + 'static,
+        shutdown_timeout: Duration,
+    ) -> ( Self, JoinHandle<backend::Result<()>> )
+    {
+        let dir = std::env::temp_dir().join( format!( "backend-app-{}", Uuid::new_v4() ) );
+        let static_dir = dir.join( "static" );
+        let assets_dir = dir.join( "assets" );
+        let photos_dir = dir.join( "photos" );
+
+        for dir in [&static_dir, &assets_dir, &photos_dir]
+        {
+            std::fs::create_dir_all( dir ).unwrap();
+        }
+
+        std::fs::write( static_dir.join( "index.html" ), INDEX_HTML ).unwrap();
+        std::fs::write( static_dir.join( "app.js" ), "console.log( 'app' );" ).unwrap();
+        std::fs::write( assets_dir.join( "robots.txt" ), "User-agent: *" ).unwrap();
+
+        let api_state = ApiState {
+            repositories:  Repositories::in_memory(),
+            photo_storage: PhotoStorage::new( photos_dir.to_str().unwrap(), DerivativeOptions {
+                sizes:   Vec::new(),
+                formats: Vec::new(),
+                quality: 80,
+            } ),
+            upload_rules:  Arc::new( UploadRules {
+                max_bytes:          MAX_UPLOAD_BYTES,
+                allowed_mime_types: vec!["image/jpeg".to_owned()],
+            } ),
+        };
+
+        let app = configure( App::builder( api_state, static_dir.to_str().unwrap(), assets_dir.to_str().unwrap() ) )
+            .build()
+            .await
+            .unwrap();
+
+        let listener = TcpListener::bind( "127.0.0.1:0" ).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = match tls_config
+        {
+            Some( tls_config ) =>
+            {
+                tokio::spawn( app.serve_tls_with_shutdown( listener, tls_config, shutdown_signal, shutdown_timeout ) )
+            }
+            None => tokio::spawn( app.serve_with_shutdown( listener, shutdown_signal, shutdown_timeout ) ),
+        };
+
+        ( Self { addr, dir }, server )
+    }
+
+    async fn get( &self, path: &str ) -> ( StatusCode, String )
+    {
+        let request = Request::get( format!( "http://{}{}", self.addr, path ) )
+            .body( Body::empty() )
+            .unwrap();
+        let ( status, _headers, body ) = self.send( request ).await;
+
+        ( status, body )
+    }
+
+    async fn send_json( &self, method: Method, path: &str, json: &str ) -> ( StatusCode, HeaderMap, String )
+    {
+        let request = Request::builder()
+            .method( method )
+            .uri( format!( "http://{}{}", self.addr, path ) )
+            .header( header::CONTENT_TYPE, "application/json" )
+            .body( Body::from( json.to_owned() ) )
+            .unwrap();
+
+        self.send( request ).await
+    }
+
+    /// Uploads a photo file with a multipart form.
+    async fn upload( &self, file_name: &str, file: &[u8] ) -> ( StatusCode, HeaderMap, String )
+    {
+        let mut form = format!(
+            "--{MULTIPART_BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{file_name}\"\r\n\
+             Content-Type: image/jpeg\r\n\r\n"
+        )
+        .into_bytes();
+        form.extend_from_slice( file );
+        form.extend_from_slice( format!( "\r\n--{MULTIPART_BOUNDARY}--\r\n" ).as_bytes() );
+
+        self.upload_form( form ).await
+    }
+
+    /// Sends a multipart form to the upload route.
+    async fn upload_form( &self, form: Vec<u8> ) -> ( StatusCode, HeaderMap, String )
+    {
+        let request = Request::post( format!( "http://{}/api/photos/upload", self.addr ) )
+            .header( header::CONTENT_TYPE, format!( "multipart/form-data; boundary={MULTIPART_BOUNDARY}" ) )
+            .body( Body::from( form ) )
+            .unwrap();
+
+        self.send( request ).await
+    }
+
+    /// Sends a json request and decodes the json response, asserting its status.
+    async fn json<T: DeserializeOwned>( &self, method: Method, path: &str, json: &str, expected: StatusCode ) -> T
+    {
+        let ( status, _headers, body ) = self.send_json( method, path, json ).await;
+        assert_eq!( status, expected, "{body}" );
+
+        serde_json::from_str( &body ).unwrap()
+    }
+
+    async fn send( &self, request: Request<Body> ) -> ( StatusCode, HeaderMap, String )
+    {
+        let response = Client::new().request( request ).await.unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = body::to_bytes( response.into_body() ).await.unwrap();
+
+        ( status, headers, String::from_utf8_lossy( &body ).into_owned() )
+    }
+
+    /// Requests over HTTP/2 with TLS, trusting the test certificate authority. Also returns the negotiated protocol.
+    async fn get_https( &self, path: &str ) -> ( StatusCode, String, Option<Vec<u8>> )
+    {
+        let mut root_store = RootCertStore::empty();
+        for cert in rustls_pemfile::certs( &mut fixture( "ca.pem" ).as_slice() ).unwrap()
+        {
+            root_store.add( &Certificate( cert ) ).unwrap();
+        }
+
+        let mut client_config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates( root_store )
+            .with_no_client_auth();
+        client_config.alpn_protocols = vec![b"h2".to_vec()];
+
+        let stream = TcpStream::connect( self.addr ).await.unwrap();
+        let stream = TlsConnector::from( Arc::new( client_config ) )
+            .connect( ServerName::try_from( "localhost" ).unwrap(), stream )
+            .await
+            .unwrap();
+        let protocol = stream.get_ref().1.alpn_protocol().map( <[u8]>::to_vec );
+
+        let ( mut sender, connection ) =
+            client::conn::Builder::new().http2_only( true ).handshake( stream ).await.unwrap();
+        tokio::spawn( connection );
+
+        let request = Request::get( format!( "https://localhost{path}" ) ).body( Body::empty() ).unwrap();
+        let response = sender.send_request( request ).await.unwrap();
+        let status = response.status();
+        let body = body::to_bytes( response.into_body() ).await.unwrap();
+
+        ( status, String::from_utf8( body.to_vec() ).unwrap(), protocol )
+    }
+}
+
+fn fixture( name: &str ) -> Vec<u8>
+{
+    std::fs::read( Path::new( env!( "CARGO_MANIFEST_DIR" ) ).join( "tests/fixtures/tls" ).join( name ) ).unwrap()
+}
+
+fn fixture_path( name: &str ) -> String
+{
+    format!( "{}/tests/fixtures/tls/{}", env!( "CARGO_MANIFEST_DIR" ), name )
+}
+
+impl Drop for TestServer
+{
+    fn drop( &mut self ) { let _ = std::fs::remove_dir_all( &self.dir ); }
+}
+
+#[tokio::test]
+async fn get__api_hello__hello_message()
+{
+    let server = TestServer::start().await;
+
+    assert_eq!( server.get( "/api/hello" ).await, ( StatusCode::OK, "hello from the backend!".to_owned() ) );
+}
+
+#[tokio::test]
+async fn get__api_albums__empty_list()
+{
+    let server = TestServer::start().await;
+    let ( status, body ) = server.get( "/api/albums" ).await;
+
+    assert_eq!( status, StatusCode::OK );
+    assert_eq!( body, "[]" );
+}
+
+#[tokio::test]
+async fn get__missing_album__not_found_problem_with_request_id()
+{
+    let server = TestServer::start().await;
+    let request = Request::get( format!( "http://{}/api/albums/{}", server.addr, Uuid::new_v4() ) )
+        .body( Body::empty() )
+        .unwrap();
+    let ( status, headers, body ) = server.send( request ).await;
+    let request_id = headers[REQUEST_ID_HEADER].to_str().unwrap();
+
+    assert_eq!( status, StatusCode::NOT_FOUND );
+    assert_eq!( headers[header::CONTENT_TYPE], PROBLEM_JSON_CONTENT_TYPE );
+    assert_eq!(
+        body,
+        format!(
+            "{{\"type\":\"urn:photo-story:problem:not_found\",\"title\":\"Resource not found\",\"status\":404,\
+             \"code\":\"not_found\",\"request_id\":\"{request_id}\"}}"
+        )
+    );
+}
+
+#[tokio::test]
+async fn malformed_requests__bad_request_problems_with_request_id()
+{
+    let server = TestServer::start().await;
+    let requests = [
+        ( Method::GET, "/api/albums/not-a-uuid", "", "Invalid URL" ),
+        ( Method::POST, "/api/albums", "{\"title\":", "Failed to parse the request body as JSON" ),
+        ( Method::POST, "/api/albums", "{\"title\":42}", "Failed to deserialize the JSON body" ),
+        ( Method::GET, "/api/photos?album_id=not-a-uuid", "", "Failed to deserialize query string" ),
+        ( Method::POST, "/api/photos/upload", "{}", "Invalid `boundary` for `multipart/form-data` request" ),
+    ];
+
+    for ( method, path, json, reason ) in requests
+    {
+        let ( status, headers, body ) = server.send_json( method, path, json ).await;
+        let problem: Problem = serde_json::from_str( &body ).unwrap();
+
+        assert_eq!( status, StatusCode::BAD_REQUEST, "{path}: {body}" );
+        assert_eq!( headers[header::CONTENT_TYPE], PROBLEM_JSON_CONTENT_TYPE );
+        assert_eq!( problem.code, ErrorCode::BadRequest );
+        assert!( problem.detail.unwrap().starts_with( reason ), "{path}: {body}" );
+        assert_eq!( problem.request_id.as_deref(), Some( headers[REQUEST_ID_HEADER].to_str().unwrap() ) );
+    }
+}
+
+#[tokio::test]
+async fn post__api_albums_blank_title__validation_problem()
+{
+    let server = TestServer::start().await;
+    let ( status, headers, body ) = server.send_json( Method::POST, "/api/albums", "{\"title\":\" \"}" ).await;
+
+    assert_eq!( status, StatusCode::UNPROCESSABLE_ENTITY );
+    assert_eq!( headers[header::CONTENT_TYPE], PROBLEM_JSON_CONTENT_TYPE );
+    assert!( body.contains( "\"code\":\"validation_failed\"" ) );
+    assert!( body.contains( "\"errors\":[{\"field\":\"title\",\"message\":\"must not be blank\"}]" ) );
+}
+
+#[tokio::test]
+async fn albums__create_update_delete__ordered_by_position()
+{
+    let server = TestServer::start().await;
+
+    let first: AlbumResponse =
+        server.json( Method::POST, "/api/albums", r#"{"title":"First"}"#, StatusCode::CREATED ).await;
+    let second: AlbumResponse =
+        server.json( Method::POST, "/api/albums", r#"{"title":"Second"}"#, StatusCode::CREATED ).await;
+    assert_eq!( ( first.position, second.position ), ( 0, 1 ) );
+
+    let first_path = format!( "/api/albums/{}", first.id );
+    let updated: AlbumResponse = server
+        .json( Method::PUT, &first_path, r#"{"title":"Last","position":2}"#, StatusCode::OK )
+        .await;
+    assert_eq!( ( updated.title.as_str(), updated.position ), ( "Last", 2 ) );
+
+    let albums: Vec<AlbumResponse> = server.json( Method::GET, "/api/albums", "", StatusCode::OK ).await;
+    assert_eq!( albums, vec![second, updated] );
+
+    assert_eq!( server.send_json( Method::DELETE, &first_path, "" ).await.0, StatusCode::NO_CONTENT );
+    assert_eq!( server.get( &first_path ).await.0, StatusCode::NOT_FOUND );
+    assert_eq!( server.send_json( Method::DELETE, &first_path, "" ).await.0, StatusCode::NOT_FOUND );
+    assert_eq!(
+        server.send_json( Method::PUT, &first_path, r#"{"title":"Gone"}"# ).await.0,
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[tokio::test]
+async fn photos__create_update_delete__album_cover_and_membership_kept_consistent()
+{
+    let server = TestServer::start().await;
+
+    let album: AlbumResponse =
+        server.json( Method::POST, "/api/albums", r#"{"title":"Trip"}"#, StatusCode::CREATED ).await;
+    let album_path = format!( "/api/albums/{}", album.id );
+    let photo_json = format!( r#"{{"title":"Sunset","album_id":"{}"}}"#, album.id );
+    let cover: PhotoResponse = server.json( Method::POST, "/api/photos", &photo_json, StatusCode::CREATED ).await;
+    let other: PhotoResponse = server.json( Method::POST, "/api/photos", &photo_json, StatusCode::CREATED ).await;
+    let loose: PhotoResponse =
+        server.json( Method::POST, "/api/photos", r#"{"title":"Loose"}"#, StatusCode::CREATED ).await;
+    assert_eq!( ( cover.position, other.position, loose.position ), ( 0, 1, 0 ) );
+
+    // The cover must be a photo of the album.
+    let problem: Problem = server
+        .json(
+            Method::PUT,
+            &album_path,
+            &format!( r#"{{"title":"Trip","cover_photo_id":"{}"}}"#, loose.id ),
+            StatusCode::UNPROCESSABLE_ENTITY,
+        )
+        .await;
+    assert_eq!( problem.errors[0].field, "cover_photo_id" );
+
+    let with_cover: AlbumResponse = server
+        .json(
+            Method::PUT,
+            &album_path,
+            &format!( r#"{{"title":"Trip","cover_photo_id":"{}"}}"#, cover.id ),
+            StatusCode::OK,
+        )
+        .await;
+    assert_eq!( with_cover.cover_photo_id, Some( cover.id ) );
+
+    // Deleting the cover photo unsets it.
+    let cover_path = format!( "/api/photos/{}", cover.id );
+    assert_eq!( server.send_json( Method::DELETE, &cover_path, "" ).await.0, StatusCode::NO_CONTENT );
+    assert_eq!( server.get( &cover_path ).await.0, StatusCode::NOT_FOUND );
+    let album: AlbumResponse = server.json( Method::GET, &album_path, "", StatusCode::OK ).await;
+    assert_eq!( album.cover_photo_id, None );
+
+    // Deleting the album keeps its photos without album.
+    assert_eq!( server.send_json( Method::DELETE, &album_path, "" ).await.0, StatusCode::NO_CONTENT );
+    let other: PhotoResponse =
+        server.json( Method::GET, &format!( "/api/photos/{}", other.id ), "", StatusCode::OK ).await;
+    assert_eq!( other.album_id, None );
+
+    // Photos can't be added to a missing album.
+    let problem: Problem =
+        server.json( Method::POST, "/api/photos", &photo_json, StatusCode::UNPROCESSABLE_ENTITY ).await;
+    assert_eq!( problem.errors[0].field, "album_id" );
+}
+
+#[tokio::test]
+async fn upload__valid_oversized_non_image_and_too_large_forms__only_valid_stored()
+{
+    let server = TestServer::start().await;
+
+    let mut jpeg = Cursor::new( Vec::new() );
+    DynamicImage::new_rgb8( 8, 8 ).write_to( &mut jpeg, ImageOutputFormat::Jpeg( 80 ) ).unwrap();
+    let jpeg = jpeg.into_inner();
+    assert!( jpeg.len() as u64 <= MAX_UPLOAD_BYTES );
+
+    let ( status, _headers, body ) = server.upload( "sunset.jpg", &jpeg ).await;
+    assert_eq!( status, StatusCode::CREATED, "{body}" );
+    let photo: PhotoResponse = serde_json::from_str( &body ).unwrap();
+    let file = photo.file.unwrap();
+    assert_eq!( ( photo.title.as_str(), file.mime_type.as_str() ), ( "sunset", "image/jpeg" ) );
+    assert_eq!( server.get( &file.url ).await.0, StatusCode::OK );
+
+    // Starts as a JPEG, rejected while streamed once above the maximum size.
+    let mut oversized = jpeg.clone();
+    oversized.resize( usize::try_from( MAX_UPLOAD_BYTES ).unwrap() + 1, 0 );
+    let ( status, _headers, body ) = server.upload( "large.jpg", &oversized ).await;
+    assert_eq!( status, StatusCode::UNPROCESSABLE_ENTITY );
+    assert!( body.contains( "must have at most 1024 bytes" ), "{body}" );
+
+    let ( status, _headers, body ) = server.upload( "script.jpg", b"<?php system($_GET['c']); ?>" ).await;
+    assert_eq!( status, StatusCode::UNPROCESSABLE_ENTITY );
+    assert!( body.contains( "must be an image of a supported type" ), "{body}" );
+
+    // The whole form is limited too, beyond the fields validation.
+    let form = format!(
+        "--{MULTIPART_BOUNDARY}\r\nContent-Disposition: form-data; name=\"description\"\r\n\r\n{}\r\n\
+         --{MULTIPART_BOUNDARY}--\r\n",
+        "a".repeat( 128 * 1024 )
+    );
+    let ( status, headers, body ) = server.upload_form( form.into_bytes() ).await;
+    assert_eq!( status, StatusCode::PAYLOAD_TOO_LARGE, "{body}" );
+    assert_eq!( headers[header::CONTENT_TYPE], PROBLEM_JSON_CONTENT_TYPE );
+    assert!( body.contains( r#""code":"payload_too_large""# ), "{body}" );
+
+    // Only the valid photo is stored, without temporary files left behind.
+    let photos: Vec<PhotoResponse> = server.json( Method::GET, "/api/photos", "", StatusCode::OK ).await;
+    assert_eq!( photos.len(), 1 );
+    assert_eq!( std::fs::read_dir( server.dir.join( "photos/.tmp" ) ).unwrap().count(), 0 );
+}
+
+#[tokio::test]
+async fn get__static_and_assets_files__file_contents()
+{
+    let server = TestServer::start().await;
+
+    assert_eq!( server.get( "/static/app.js" ).await, ( StatusCode::OK, "console.log( 'app' );".to_owned() ) );
+    assert_eq!( server.get( "/robots.txt" ).await, ( StatusCode::OK, "User-agent: *".to_owned() ) );
+    assert_eq!( server.get( "/static/missing.js" ).await.0, StatusCode::NOT_FOUND );
+}
+
+#[cfg( feature = "ssr" )]
+#[tokio::test]
+async fn get__home_page__rendered_in_index_html()
+{
+    let server = TestServer::start().await;
+    let ( status, body ) = server.get( "/" ).await;
+
+    assert_eq!( status, StatusCode::OK );
+    assert!( body.starts_with( "<!DOCTYPE html>" ) );
+    assert!( body.contains( "<title>Photo Story</title>" ) );
+    assert!( !body.contains( "<title>Index</title>" ) );
+    assert!( body.contains( "Home" ) );
+    assert!( body.ends_with( "<script src=\"/static/app.js\"></script></body></html>" ) );
+}
+
+#[cfg( feature = "ssr" )]
+#[tokio::test]
+async fn get__unknown_page__rendered_not_found()
+{
+    let server = TestServer::start().await;
+    let ( status, body ) = server.get( "/missing-page" ).await;
+
+    assert_eq!( status, StatusCode::NOT_FOUND );
+    assert!( body.contains( "<title>Page not found | Photo Story</title>" ) );
+}
+
+#[cfg( feature = "ssr" )]
+#[tokio::test]
+async fn get__unknown_nested_page__rendered_not_found()
+{
+    let server = TestServer::start().await;
+    let ( status, body ) = server.get( "/hello-server/missing/page" ).await;
+
+    assert_eq!( status, StatusCode::NOT_FOUND );
+    assert!( body.contains( "<title>Page not found | Photo Story</title>" ) );
+    assert!( body.contains( "<h1>404</h1>" ) );
+    assert!( body.ends_with( "<script src=\"/static/app.js\"></script></body></html>" ) );
+}
+
+#[cfg( not( feature = "ssr" ) )]
+#[tokio::test]
+async fn get__unknown_page__index_html()
+{
+    let server = TestServer::start().await;
+
+    assert_eq!( server.get( "/albums/trip" ).await, ( StatusCode::OK, INDEX_HTML.to_owned() ) );
+}
+
+#[tokio::test]
+async fn get__unknown_api_paths__not_found_problem()
+{
+    let server = TestServer::start().await;
+
+    for path in ["/api", "/api/missing", "/api/missing/nested"]
+    {
+        let request = Request::get( format!( "http://{}{path}", server.addr ) ).body( Body::empty() ).unwrap();
+        let ( status, headers, body ) = server.send( request ).await;
+
+        assert_eq!( status, StatusCode::NOT_FOUND, "{path}" );
+        assert_eq!( headers[header::CONTENT_TYPE], PROBLEM_JSON_CONTENT_TYPE, "{path}" );
+        assert!( body.contains( r#""code":"not_found""# ), "{path}" );
+    }
+}
+
+/// Router with a `/slow` route answering after a delay.
+fn slow_route( delay: Duration ) -> Router
+{
+    Router::new().route(
+        "/slow",
+        get( move || async move {
+            tokio::time::sleep( delay ).await;
+            "slow"
+        } ),
+    )
+}
+
+#[tokio::test]
+async fn serve_with_shutdown__in_flight_request__drained()
+{
+    let ( shutdown_tx, shutdown_rx ) = oneshot::channel::<()>();
+    let ( server, serving ) = TestServer::start_with(
+        |builder| builder.merge( slow_route( Duration::from_millis( 200 ) ) ),
+        None,
+        async move {
+            let _ = shutdown_rx.await;
+        },
+        Duration::from_secs( 5 ),
+    )
+    .await;
+
+    let request = tokio::spawn( async move { server.get( "/slow" ).await } );
+    tokio::time::sleep( Duration::from_millis( 50 ) ).await;
+    shutdown_tx.send( () ).unwrap();
+
+    assert_eq!( request.await.unwrap(), ( StatusCode::OK, "slow".to_owned() ) );
+    assert!( serving.await.unwrap().is_ok() );
+}
+
+#[tokio::test]
+async fn serve_with_shutdown__request_longer_than_timeout__error()
+{
+    let ( shutdown_tx, shutdown_rx ) = oneshot::channel::<()>();
+    let ( server, serving ) = TestServer::start_with(
+        |builder| builder.merge( slow_route( Duration::from_secs( 10 ) ) ),
+        None,
+        async move {
+            let _ = shutdown_rx.await;
+        },
+        Duration::from_millis( 100 ),
+    )
+    .await;
+
+    let _request = tokio::spawn( async move { server.get( "/slow" ).await } );
+    tokio::time::sleep( Duration::from_millis( 50 ) ).await;
+    shutdown_tx.send( () ).unwrap();
+
+    assert!( serving.await.unwrap().is_err() );
+}
+
+#[tokio::test]
+async fn serve_tls_with_shutdown__https_request__served_over_http2()
+{
+    let resolver = CertificateResolver::new( &fixture_path( "cert.pem" ), &fixture_path( "cert.key" ) ).unwrap();
+    let alpn_protocols = ["h2".to_owned(), "http/1.1".to_owned()];
+    let tls_config = tls::server_config( Arc::new( resolver ), TlsVersion::Tls12, &alpn_protocols ).unwrap();
+    let ( server, _serving ) = TestServer::start_with(
+        |builder| builder,
+        Some( Arc::new( tls_config ) ),
+        future::pending(),
+        Duration::ZERO,
+    )
+    .await;
+
+    assert_eq!(
+        server.get_https( "/api/hello" ).await,
+        ( StatusCode::OK, "hello from the backend!".to_owned(), Some( b"h2".to_vec() ) )
+    );
+}
+
+#[tokio::test]
+async fn redirect_router__http_request__permanent_redirect_to_https()
+{
+    let listener = TcpListener::bind( "127.0.0.1:0" ).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let redirect_server = axum::Server::from_tcp( listener ).unwrap();
+    tokio::spawn( redirect_server.serve( tls::redirect_router( 8443 ).into_make_service() ) );
+
+    let request = Request::get( format!( "http://{addr}/albums?page=2" ) )
+        .header( header::HOST, "example.com:8080" )
+        .body( Body::empty() )
+        .unwrap();
+    let response = Client::new().request( request ).await.unwrap();
+
+    assert_eq!( response.status(), StatusCode::PERMANENT_REDIRECT );
+    assert_eq!( response.headers()[header::LOCATION], "https://example.com:8443/albums?page=2" );
+}
+
+#[tokio::test]
+async fn get__health_live__up()
+{
+    let server = TestServer::start().await;
+
+    assert_eq!( server.get( "/health/live" ).await, ( StatusCode::OK, r#"{"status":"up"}"#.to_owned() ) );
+}
+
+#[tokio::test]
+async fn get__health_ready_with_failing_check__down_with_check_reports()
+{
+    let ( server, _serving ) = TestServer::start_with(
+        |builder| {
+            builder
+                .health_check( "cache", || async { Ok( () ) } )
+                .health_check( "queue", || async { Err( color_eyre::eyre::eyre!( "Queue unreachable" ) ) } )
+        },
+        None,
+        future::pending(),
+        Duration::ZERO,
+    )
+    .await;
+
+    let ( status, body ) = server.get( "/health/ready" ).await;
+
+    assert_eq!( status, StatusCode::SERVICE_UNAVAILABLE );
+    assert!( body.starts_with( r#"{"status":"down","checks":[{"name":"cache","status":"up","latency_ms":"# ) );
+    assert!( body.contains( r#"{"name":"static_dir","status":"up","# ) );
+    assert!( body.contains( r#"{"name":"index_html","status":"up","# ) );
+    assert!( body.contains( r#""name":"queue","status":"down","latency_ms":"# ) );
+    assert!( body.contains( r#""reason":"down"}"# ) );
+    assert!( !body.contains( "Queue unreachable" ) );
+}
+
+#[tokio::test]
+async fn get__health_ready__up()
+{
+    let server = TestServer::start().await;
+    let ( status, body ) = server.get( "/health/ready" ).await;
+
+    assert_eq!( status, StatusCode::OK );
+    assert!( body.starts_with( r#"{"status":"up","checks":[{"name":"static_dir","status":"up","latency_ms":"# ) );
+}
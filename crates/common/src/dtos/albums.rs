@@ -0,0 +1,32 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Album as returned by the api.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlbumResponse
+{
+    pub id:             Uuid,
+    pub title:          String,
+    pub description:    Option<String>,
+    pub position:       u32,
+    pub cover_photo_id: Option<Uuid>,
+    pub created_at:     DateTime<Utc>,
+    pub updated_at:     DateTime<Utc>,
+}
+
+/// Body of the album create and update requests.
+///
+/// On create, the album is placed after the existing ones if `position` is not set. On update, the
+/// album keeps its position if `position` is not set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlbumRequest
+{
+    pub title:          String,
+    #[serde( default )]
+    pub description:    Option<String>,
+    #[serde( default )]
+    pub position:       Option<u32>,
+    #[serde( default )]
+    pub cover_photo_id: Option<Uuid>,
+}
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Content type of the api error responses, see [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807).
+pub const PROBLEM_JSON_CONTENT_TYPE: &str = "application/problem+json";
+
+/// Codes of the api errors, clients match on them rather than on the titles and details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde( rename_all = "snake_case" )]
+pub enum ErrorCode
+{
+    BadRequest,
+    NotFound,
+    PayloadTooLarge,
+    ValidationFailed,
+    Internal,
+    /// Code added by a newer backend.
+    #[serde( other )]
+    Unknown,
+}
+
+impl ErrorCode
+{
+    #[must_use]
+    pub const fn as_str( self ) -> &'static str
+    {
+        match self
+        {
+            Self::BadRequest => "bad_request",
+            Self::NotFound => "not_found",
+            Self::PayloadTooLarge => "payload_too_large",
+            Self::ValidationFailed => "validation_failed",
+            Self::Internal => "internal",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    /// Http status code answered with the error.
+    #[must_use]
+    pub const fn status( self ) -> u16
+    {
+        match self
+        {
+            Self::BadRequest => 400,
+            Self::NotFound => 404,
+            Self::PayloadTooLarge => 413,
+            Self::ValidationFailed => 422,
+            Self::Internal | Self::Unknown => 500,
+        }
+    }
+
+    /// Short summary of the error, the same for all its occurrences.
+    #[must_use]
+    pub const fn title( self ) -> &'static str
+    {
+        match self
+        {
+            Self::BadRequest => "Bad request",
+            Self::NotFound => "Resource not found",
+            Self::PayloadTooLarge => "Payload too large",
+            Self::ValidationFailed => "Validation failed",
+            Self::Internal | Self::Unknown => "Internal server error",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode
+{
+    fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result { f.write_str( self.as_str() ) }
+}
+
+/// Validation error of a single request field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError
+{
+    pub field:   String,
+    pub message: String,
+}
+
+/// Body of the api error responses, an RFC 7807 problem details object extended with the error code, the id of
+/// the request to find it in the server logs, and the field errors of the failed validations.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Problem
+{
+    #[serde( rename = "type" )]
+    pub type_uri:   String,
+    pub title:      String,
+    pub status:     u16,
+    #[serde( default, skip_serializing_if = "Option::is_none" )]
+    pub detail:     Option<String>,
+    pub code:       ErrorCode,
+    #[serde( default, skip_serializing_if = "Option::is_none" )]
+    pub request_id: Option<String>,
+    #[serde( default, skip_serializing_if = "Vec::is_empty" )]
+    pub errors:     Vec<FieldError>,
+}
+
+impl Problem
+{
+    #[must_use]
+    pub fn new( code: ErrorCode, detail: Option<String> ) -> Self
+    {
+        Self {
+            type_uri: format!( "urn:photo-story:problem:{code}" ),
+            title: code.title().to_owned(),
+            status: code.status(),
+            detail,
+            code,
+            request_id: None,
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for Problem
+{
+    fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+    {
+        write!( f, "{}", self.title )?;
+
+        if let Some( detail ) = &self.detail
+        {
+            write!( f, ": {detail}" )?;
+        }
+
+        for error in &self.errors
+        {
+            write!( f, "; {} {}", error.field, error.message )?;
+        }
+
+        if let Some( request_id ) = &self.request_id
+        {
+            write!( f, " (request {request_id})" )?;
+        }
+
+        Ok( () )
+    }
+}
@@ -0,0 +1,5 @@
+//! Request and response data transfer objects shared by the backend api and the frontend.
+
+pub mod albums;
+pub mod errors;
+pub mod photos;
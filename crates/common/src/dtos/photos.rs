@@ -0,0 +1,116 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Photo as returned by the api.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhotoResponse
+{
+    pub id:          Uuid,
+    pub album_id:    Option<Uuid>,
+    pub title:       String,
+    pub description: Option<String>,
+    pub position:    u32,
+    pub taken_at:    Option<DateTime<Utc>>,
+    pub file:        Option<PhotoFileResponse>,
+    pub created_at:  DateTime<Utc>,
+    pub updated_at:  DateTime<Utc>,
+}
+
+/// Original file of a photo as returned by the api.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhotoFileResponse
+{
+    pub url:           String,
+    pub mime_type:     String,
+    pub size_bytes:    u64,
+    pub original_name: Option<String>,
+    /// Resized and re-encoded copies of the original, smallest first.
+    #[serde( default )]
+    pub derivatives:   Vec<PhotoDerivativeResponse>,
+    #[serde( default )]
+    pub metadata:      PhotoMetadataResponse,
+}
+
+/// Camera metadata of the original file of a photo, read from its EXIF and XMP data.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PhotoMetadataResponse
+{
+    pub camera_make:        Option<String>,
+    pub camera_model:       Option<String>,
+    pub lens_model:         Option<String>,
+    pub focal_length_mm:    Option<f64>,
+    pub f_number:           Option<f64>,
+    pub exposure_time_secs: Option<f64>,
+    pub iso:                Option<u32>,
+    pub taken_at:           Option<DateTime<Utc>>,
+    pub gps:                Option<GpsPositionResponse>,
+    /// EXIF orientation, from 1 to 8. It is already applied to the derivatives.
+    pub orientation:        Option<u16>,
+    /// Rating, from 0 to 5.
+    pub rating:             Option<u8>,
+}
+
+/// Position where a photo was taken, in degrees.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GpsPositionResponse
+{
+    pub latitude:   f64,
+    pub longitude:  f64,
+    /// Meters above sea level.
+    pub altitude_m: Option<f64>,
+}
+
+/// Resized and re-encoded copy of the original file of a photo.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhotoDerivativeResponse
+{
+    /// Name of the derivative size, e.g. `thumbnail`.
+    pub name:       String,
+    pub url:        String,
+    pub mime_type:  String,
+    pub width:      u32,
+    pub height:     u32,
+    pub size_bytes: u64,
+}
+
+/// Body of the photo create and update requests.
+///
+/// On create, the photo is placed after the existing ones of its album if `position` is not set. On
+/// update, the photo keeps its position if `position` is not set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhotoRequest
+{
+    #[serde( default )]
+    pub album_id:    Option<Uuid>,
+    pub title:       String,
+    #[serde( default )]
+    pub description: Option<String>,
+    #[serde( default )]
+    pub position:    Option<u32>,
+    #[serde( default )]
+    pub taken_at:    Option<DateTime<Utc>>,
+}
+
+/// Multipart form of the photo upload request.
+///
+/// The `file` part holds the image. The other parts are text fields with the same meaning as in
+/// [`PhotoRequest`], `title` defaults to the uploaded file name. `taken_at` is an RFC 3339 date.
+pub mod upload_fields
+{
+    pub const FILE: &str = "file";
+    pub const ALBUM_ID: &str = "album_id";
+    pub const TITLE: &str = "title";
+    pub const DESCRIPTION: &str = "description";
+    pub const POSITION: &str = "position";
+    pub const TAKEN_AT: &str = "taken_at";
+}
+
+/// Query of the photo list request.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhotoListQuery
+{
+    /// Only list the photos of this album.
+    #[serde( default )]
+    pub album_id: Option<Uuid>,
+}
@@ -4,4 +4,5 @@
 #![warn( clippy::complexity )]
 #![warn( clippy::perf )]
 
+pub mod dtos;
 pub mod settings;
@@ -0,0 +1,321 @@
+#![deny( clippy::all )]
+#![warn( clippy::pedantic )]
+#![warn( clippy::nursery )]
+#![warn( clippy::complexity )]
+#![warn( clippy::perf )]
+
+//! Derive macros of the frontend components.
+//!
+//! The generated code refers to `crate::features::base_component`, so the macros are meant to be used
+//! inside the `frontend` crate only.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Expr, Fields, LitStr};
+
+/// Derives `Settings` for a struct with named fields, along with its `Default` and its conversion into a
+/// `SettingValue::HashMap`, so that it can be nested into other settings.
+///
+/// Each field is converted with `SettingValue::from` and parsed from text with `SettingParse`. Field
+/// attributes:
+/// - `#[settings( rename = "key" )]`: key of the setting, the field name otherwise.
+/// - `#[settings( default = expr )]`: default value, `Default::default()` otherwise.
+/// - `#[settings( nested )]`: the field is itself `Settings`, its settings are set with `key.nested_key`.
+///
+/// ```ignore
+/// #[derive(Clone, PartialEq, Settings)]
+/// pub struct LightboxItemSettings
+/// {
+///     #[settings( rename = "zoomable", default = true )]
+///     pub is_zoomable: bool,
+///     #[settings( default = 4.0 )]
+///     pub max_zoom_scale: f64,
+/// }
+/// ```
+#[proc_macro_derive( Settings, attributes( settings ) )]
+pub fn derive_settings( input: TokenStream ) -> TokenStream
+{
+    let input = parse_macro_input!( input as DeriveInput );
+
+    expand_settings( &input ).unwrap_or_else( syn::Error::into_compile_error ).into()
+}
+
+/// Settings attributes of a field.
+struct FieldSettings
+{
+    key:       LitStr,
+    default:   Option<Expr>,
+    is_nested: bool,
+}
+
+fn expand_settings( input: &DeriveInput ) -> syn::Result<TokenStream2>
+{
+    let fields = match &input.data
+    {
+        Data::Struct( data ) => match &data.fields
+        {
+            Fields::Named( fields ) => &fields.named,
+            _ => return Err( syn::Error::new_spanned( input, "Settings can only be derived for named fields" ) ),
+        },
+        _ => return Err( syn::Error::new_spanned( input, "Settings can only be derived for structs" ) ),
+    };
+
+    let name = &input.ident;
+    let ( impl_generics, type_generics, where_clause ) = input.generics.split_for_impl();
+    let base = quote! { crate::features::base_component };
+
+    let mut inserts = Vec::new();
+    let mut setters = Vec::new();
+    let mut defaults = Vec::new();
+
+    for field in fields
+    {
+        let ident = field.ident.as_ref().expect( "named fields have an ident" );
+        let ty = &field.ty;
+        let settings = field_settings( field )?;
+        let key = &settings.key;
+
+        inserts.push( quote! {
+            map.insert( #key, #base::SettingValue::from( ::std::clone::Clone::clone( &self.#ident ) ) );
+        } );
+
+        if settings.is_nested
+        {
+            let prefix = LitStr::new( &format!( "{}.", key.value() ), key.span() );
+
+            setters.push( quote! {
+                _ if key.starts_with( #prefix ) =>
+                {
+                    #base::Settings::set_setting( &mut self.#ident, &key[#prefix.len()..], value )
+                }
+            } );
+        }
+        else
+        {
+            setters.push( quote! {
+                #key => match <#ty as #base::SettingParse>::parse_setting( value )
+                {
+                    ::std::option::Option::Some( value ) =>
+                    {
+                        self.#ident = value;
+                        true
+                    }
+                    ::std::option::Option::None => false,
+                },
+            } );
+        }
+
+        let default = settings.default.as_ref().map_or_else(
+            || quote! { ::std::default::Default::default() },
+            |default| quote! { ::std::convert::Into::into( #default ) },
+        );
+        defaults.push( quote! { #ident: #default, } );
+    }
+
+    Ok( quote! {
+        impl #impl_generics #base::Settings for #name #type_generics #where_clause
+        {
+            fn to_hashmap( &self ) -> #base::SettingsHashmap
+            {
+                let mut map = #base::SettingsHashmap::new();
+                #( #inserts )*
+                map
+            }
+
+            fn set_setting( &mut self, key: &str, value: &str ) -> bool
+            {
+                match key
+                {
+                    #( #setters )*
+                    _ => false,
+                }
+            }
+        }
+
+        impl #impl_generics ::std::default::Default for #name #type_generics #where_clause
+        {
+            fn default() -> Self
+            {
+                Self {
+                    #( #defaults )*
+                }
+            }
+        }
+
+        impl #impl_generics ::std::convert::From<#name #type_generics> for #base::SettingValue #where_clause
+        {
+            fn from( settings: #name #type_generics ) -> Self
+            {
+                Self::HashMap( #base::Settings::to_hashmap( &settings ) )
+            }
+        }
+    } )
+}
+
+fn field_settings( field: &syn::Field ) -> syn::Result<FieldSettings>
+{
+    let ident = field.ident.as_ref().expect( "named fields have an ident" );
+    let mut settings = FieldSettings {
+        key:       LitStr::new( &ident.to_string(), ident.span() ),
+        default:   None,
+        is_nested: false,
+    };
+
+    for attr in field.attrs.iter().filter( |attr| attr.path().is_ident( "settings" ) )
+    {
+        attr.parse_nested_meta( |meta| {
+            if meta.path.is_ident( "rename" )
+            {
+                settings.key = meta.value()?.parse()?;
+            }
+            else if meta.path.is_ident( "default" )
+            {
+                settings.default = Some( meta.value()?.parse()? );
+            }
+            else if meta.path.is_ident( "nested" )
+            {
+                settings.is_nested = true;
+            }
+            else
+            {
+                return Err( meta.error( "expected `rename`, `default` or `nested`" ) );
+            }
+
+            Ok( () )
+        } )?;
+    }
+
+    Ok( settings )
+}
+
+#[cfg( test )]
+#[allow( non_snake_case )]
+mod tests
+{
+    use super::*;
+
+    use syn::{parse_quote, Field, FieldsNamed};
+
+    fn field( fields: FieldsNamed ) -> Field { fields.named.into_iter().next().unwrap() }
+
+    fn error( field: &Field ) -> String { field_settings( field ).err().unwrap().to_string() }
+
+    /// Parses the tokens of a derive input, as given to the derive macro, and expands it.
+    fn expand( tokens: TokenStream2 ) -> syn::Result<TokenStream2> { expand_settings( &syn::parse2( tokens )? ) }
+
+    #[test]
+    fn field_settings__no_attribute__field_name_and_default_value()
+    {
+        let settings = field_settings( &field( parse_quote!( { max_zoom_scale: f64 } ) ) ).unwrap();
+
+        assert_eq!( settings.key.value(), "max_zoom_scale" );
+        assert!( settings.default.is_none() );
+        assert!( !settings.is_nested );
+    }
+
+    #[test]
+    fn field_settings__rename_default_and_nested__parsed()
+    {
+        let settings = field_settings( &field( parse_quote!( {
+            #[settings( rename = "zoomable", default = true )]
+            #[settings( nested )]
+            #[doc = "Not a settings attribute."]
+            is_zoomable: bool
+        } ) ) )
+        .unwrap();
+
+        assert_eq!( settings.key.value(), "zoomable" );
+        assert_eq!( settings.default.map( |default| quote!( #default ).to_string() ).as_deref(), Some( "true" ) );
+        assert!( settings.is_nested );
+    }
+
+    #[test]
+    fn field_settings__invalid_attributes__errors()
+    {
+        assert_eq!(
+            error( &field( parse_quote!( { #[settings( renamed = "zoomable" )] is_zoomable: bool } ) ) ),
+            "expected `rename`, `default` or `nested`",
+        );
+        assert_eq!(
+            error( &field( parse_quote!( { #[settings( rename = zoomable )] is_zoomable: bool } ) ) ),
+            "expected string literal",
+        );
+        assert_eq!( error( &field( parse_quote!( { #[settings( default )] is_zoomable: bool } ) ) ), "expected `=`" );
+    }
+
+    #[test]
+    fn expand_settings__not_named_fields__errors()
+    {
+        let errors = [
+            parse_quote!( struct Settings( bool ); ),
+            parse_quote!( struct Settings; ),
+            parse_quote!( enum Settings { Zoomable } ),
+        ]
+        .map( |input: DeriveInput| expand_settings( &input ).err().unwrap().to_string() );
+
+        assert_eq!( errors, [
+            "Settings can only be derived for named fields",
+            "Settings can only be derived for named fields",
+            "Settings can only be derived for structs",
+        ] );
+    }
+
+    #[test]
+    fn expand_settings__invalid_settings_attributes__errors()
+    {
+        let errors = [
+            quote!( struct ItemSettings { #[settings( renamed = "zoomable" )] is_zoomable: bool } ),
+            quote!( struct ItemSettings { #[settings( rename = zoomable )] is_zoomable: bool } ),
+            quote!( struct ItemSettings { #[settings( default )] is_zoomable: bool } ),
+            quote!( struct ItemSettings { #[settings( default = )] is_zoomable: bool } ),
+            quote!( struct ItemSettings { #[settings] is_zoomable: bool } ),
+            quote!( struct ItemSettings { #[settings = "zoomable"] is_zoomable: bool } ),
+        ]
+        .map( |tokens| expand( tokens ).err().unwrap().to_string() );
+
+        assert_eq!( errors, [
+            "expected `rename`, `default` or `nested`",
+            "expected string literal",
+            "expected `=`",
+            "unexpected end of input, expected an expression",
+            "expected attribute arguments in parentheses: #[settings(...)]",
+            "expected parentheses: #[settings(...)]",
+        ] );
+    }
+
+    #[test]
+    fn expand_settings__named_fields__settings_default_and_setting_value_impls()
+    {
+        let expanded = expand( quote!(
+            struct ItemSettings
+            {
+                #[settings( rename = "zoomable", default = true )]
+                is_zoomable: bool,
+                #[settings( nested )]
+                caption: CaptionSettings,
+            }
+        ) )
+        .unwrap();
+
+        let impls = syn::parse2::<syn::File>( expanded )
+            .unwrap()
+            .items
+            .into_iter()
+            .map( |item| match item
+            {
+                syn::Item::Impl( item ) => ( item.trait_.unwrap().1, *item.self_ty ),
+                _ => panic!( "expected only impls" ),
+            } )
+            .collect::<Vec<( syn::Path, syn::Type )>>();
+
+        assert_eq!( impls, [
+            ( parse_quote!( crate::features::base_component::Settings ), parse_quote!( ItemSettings ) ),
+            ( parse_quote!( ::std::default::Default ), parse_quote!( ItemSettings ) ),
+            (
+                parse_quote!( ::std::convert::From<ItemSettings> ),
+                parse_quote!( crate::features::base_component::SettingValue ),
+            ),
+        ] );
+    }
+}
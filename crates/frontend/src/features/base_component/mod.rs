@@ -1,62 +1,377 @@
-use std::collections::HashMap;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 use yew::prelude::*;
 
-macro_rules! struct_to_hashmap {
-    ( $struct_name:ident { $( $field_name:ident : $field_type:ty ),* $(,)? } ) => {
-        impl Settings for $struct_name
+pub use component_derive::Settings;
+
+/// Typed settings of a component, derived with `#[derive(Settings)]`.
+pub trait Settings
+{
+    fn to_hashmap( &self ) -> SettingsHashmap;
+
+    /// Sets a setting from text, e.g. an html attribute. Nested settings are set with `key.nested_key`.
+    /// Returns `false` if the key is unknown or the value can't be parsed.
+    fn set_setting( &mut self, key: &str, value: &str ) -> bool;
+
+    /// Overrides settings from `data-*` attributes, e.g. `data-max-zoom-scale="2"` sets `max_zoom_scale`.
+    /// Other attributes are ignored.
+    fn override_from_data_attributes<'a>( &mut self, attributes: impl IntoIterator<Item = ( &'a str, &'a str )> )
+    where
+        Self: Sized,
+    {
+        for ( name, value ) in attributes
         {
-            fn to_hashmap( &self, key: &str ) -> SettingsHashmap
+            if let Some( key ) = name.strip_prefix( "data-" )
             {
-                let mut map = SettingsHashmap::new();
-                map.insert( key.to_string(), self.clone().into() );
-                map
+                self.set_setting( &key.replace( '-', "_" ), value );
             }
         }
+    }
+}
+
+pub type SettingsHashmap = HashMap<&'static str, SettingValue>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingValue
+{
+    None,
+    Integer( i64 ),
+    Float( f64 ),
+    String( String ),
+    Bool( bool ),
+    HashMap( SettingsHashmap ),
+}
+
+/// Value of a setting that can be parsed from text, e.g. from an html attribute.
+pub trait SettingParse: Sized
+{
+    fn parse_setting( value: &str ) -> Option<Self>;
+}
+
+macro_rules! impl_setting_value {
+    ( $variant:ident( $value_type:ty ): $( $type:ty ),+ ) => {
+        $(
+            impl From<$type> for SettingValue
+            {
+                fn from( value: $type ) -> Self { Self::$variant( <$value_type>::from( value ) ) }
+            }
+
+            impl SettingParse for $type
+            {
+                fn parse_setting( value: &str ) -> Option<Self> { value.trim().parse().ok() }
+            }
+        )+
     };
 }
 
-pub trait Settings
+impl_setting_value!( Integer( i64 ): i8, i16, i32, i64, u8, u16, u32 );
+impl_setting_value!( Float( f64 ): f32, f64 );
+impl From<String> for SettingValue
 {
-    fn to_hashmap( &self ) -> SettingsHashmap;
+    fn from( value: String ) -> Self { Self::String( value ) }
 }
 
-pub type SettingsHashmap = HashMap<&'static str, SettingValue<'static>>;
+impl SettingParse for String
+{
+    fn parse_setting( value: &str ) -> Option<Self> { Some( value.to_owned() ) }
+}
 
-pub enum SettingValue<'a>
+impl From<&str> for SettingValue
 {
-    U32( u32 ),
-    String( &'a str ),
-    Bool( bool ),
-    HashMap( HashMap<String, SettingValue<'a>> ),
+    fn from( value: &str ) -> Self { Self::String( value.to_owned() ) }
+}
+
+impl From<bool> for SettingValue
+{
+    fn from( value: bool ) -> Self { Self::Bool( value ) }
+}
+
+/// An attribute without value, e.g. `data-zoomable`, is true.
+impl SettingParse for bool
+{
+    fn parse_setting( value: &str ) -> Option<Self>
+    {
+        match value.trim()
+        {
+            "" | "true" => Some( true ),
+            "false" => Some( false ),
+            _ => None,
+        }
+    }
+}
+
+impl<T> From<Option<T>> for SettingValue
+where
+    T: Into<Self>,
+{
+    fn from( value: Option<T> ) -> Self { value.map_or( Self::None, Into::into ) }
+}
+
+/// An empty value is `None`.
+impl<T: SettingParse> SettingParse for Option<T>
+{
+    fn parse_setting( value: &str ) -> Option<Self>
+    {
+        if value.trim().is_empty()
+        {
+            Some( None )
+        }
+        else
+        {
+            T::parse_setting( value ).map( Some )
+        }
+    }
 }
 
-pub struct BasePluginComponent {}
+/// Event emitted by a plugin component, e.g. the lightbox opening.
+pub trait PluginEvent: Clone + 'static
+{
+    /// Name listeners subscribe to, e.g. `"open"`.
+    fn name( &self ) -> &'static str;
+}
 
-impl BasePluginComponent
+struct Listener<E>
 {
-    pub(crate) fn new( settings: Option<Box<dyn Settings>> ) -> Self { Self {} }
+    callback: Callback<E>,
+    is_once:  bool,
+}
+
+type ListenersByEvent<E> = HashMap<String, Vec<Listener<E>>>;
 
-    // fn on( &self, event_name: &str, callback: Callback<()> ) -> Self
-    // {
-    //     event_name.split_whitespace().for_each( |event_name| {
-    //         unimplemented!();
-    //     } );
-    //
-    //     Self
-    // }
-    //
-    // fn once( &self, event_name: &str, callback: Callback<()> ) -> Self
-    // {
-    //     Self
-    // }
-    //
-    // fn off( &self, event_name: &str, callback: Callback<()> ) -> Self
-    // {
-    //     Self
-    // }
+/// Settings and events shared by the plugin components, e.g. the lightbox.
+///
+/// Clones share the same listeners, so the application can keep a clone to subscribe to the events
+/// emitted by a component.
+pub struct BasePluginComponent<E: PluginEvent>
+{
+    settings:  Rc<SettingsHashmap>,
+    listeners: Rc<RefCell<ListenersByEvent<E>>>,
 }
 
-impl From<u32> for SettingValue<'static>
+// The listeners methods return the component only to chain calls, they don't need to be used.
+#[allow( clippy::must_use_candidate )]
+impl<E: PluginEvent> BasePluginComponent<E>
 {
-    fn from( value: u32 ) -> Self { Self::U32( value ) }
+    #[must_use]
+    pub fn new( settings: Option<Box<dyn Settings>> ) -> Self
+    {
+        Self {
+            settings:  Rc::new( settings.map( |settings| settings.to_hashmap() ).unwrap_or_default() ),
+            listeners: Rc::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn setting( &self, key: &str ) -> Option<&SettingValue> { self.settings.get( key ) }
+
+    /// Calls `callback` on each of the space separated events, e.g. `"open close"`.
+    pub fn on( &self, event_names: &str, callback: &Callback<E> ) -> &Self { self.add( event_names, callback, false ) }
+
+    /// Calls `callback` on the first of each of the space separated events only.
+    pub fn once( &self, event_names: &str, callback: &Callback<E> ) -> &Self { self.add( event_names, callback, true ) }
+
+    /// Stops calling `callback` on each of the space separated events.
+    pub fn off( &self, event_names: &str, callback: &Callback<E> ) -> &Self
+    {
+        let mut listeners = self.listeners.borrow_mut();
+
+        for event_name in event_names.split_whitespace()
+        {
+            if let Some( event_listeners ) = listeners.get_mut( event_name )
+            {
+                event_listeners.retain( |listener| listener.callback != *callback );
+
+                if event_listeners.is_empty()
+                {
+                    listeners.remove( event_name );
+                }
+            }
+        }
+
+        self
+    }
+
+    /// Removes all the listeners, e.g. when the component emitting the events is destroyed.
+    pub fn off_all( &self ) { self.listeners.borrow_mut().clear(); }
+
+    /// Calls the listeners of an event.
+    pub fn emit( &self, event: &E )
+    {
+        // Take the callbacks out first, so that they can subscribe and unsubscribe.
+        let callbacks = {
+            let mut listeners = self.listeners.borrow_mut();
+            let Some( event_listeners ) = listeners.get_mut( event.name() ) else { return };
+
+            let callbacks = event_listeners
+                .iter()
+                .map( |listener| listener.callback.clone() )
+                .collect::<Vec<_>>();
+            event_listeners.retain( |listener| !listener.is_once );
+
+            if event_listeners.is_empty()
+            {
+                listeners.remove( event.name() );
+            }
+
+            callbacks
+        };
+
+        for callback in callbacks
+        {
+            callback.emit( event.clone() );
+        }
+    }
+
+    fn add( &self, event_names: &str, callback: &Callback<E>, is_once: bool ) -> &Self
+    {
+        let mut listeners = self.listeners.borrow_mut();
+
+        for event_name in event_names.split_whitespace()
+        {
+            listeners.entry( event_name.to_owned() ).or_default().push( Listener {
+                callback: callback.clone(),
+                is_once,
+            } );
+        }
+
+        self
+    }
+}
+
+impl<E: PluginEvent> Clone for BasePluginComponent<E>
+{
+    fn clone( &self ) -> Self
+    {
+        Self {
+            settings:  self.settings.clone(),
+            listeners: self.listeners.clone(),
+        }
+    }
+}
+
+impl<E: PluginEvent> Default for BasePluginComponent<E>
+{
+    fn default() -> Self { Self::new( None ) }
+}
+
+/// Clones are equal, they share the same listeners.
+impl<E: PluginEvent> PartialEq for BasePluginComponent<E>
+{
+    fn eq( &self, other: &Self ) -> bool { Rc::ptr_eq( &self.listeners, &other.listeners ) }
+}
+
+#[cfg( test )]
+#[allow( non_snake_case )]
+mod tests
+{
+    use super::*;
+    use std::cell::Cell;
+
+    #[derive(Clone)]
+    enum TestEvent
+    {
+        Open,
+        Close,
+    }
+
+    impl PluginEvent for TestEvent
+    {
+        fn name( &self ) -> &'static str
+        {
+            match self
+            {
+                Self::Open => "open",
+                Self::Close => "close",
+            }
+        }
+    }
+
+    fn counter() -> ( Rc<Cell<u32>>, Callback<TestEvent> )
+    {
+        let count = Rc::new( Cell::new( 0 ) );
+        let callback = {
+            let count = count.clone();
+            Callback::from( move |_| count.set( count.get() + 1 ) )
+        };
+
+        ( count, callback )
+    }
+
+    #[derive(Debug, Clone, PartialEq, Settings)]
+    struct TestSettings
+    {
+        #[settings( rename = "zoomable", default = true )]
+        is_zoomable: bool,
+        caption:     Option<String>,
+        #[settings( nested )]
+        slideshow:   TestSlideshowSettings,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Settings)]
+    struct TestSlideshowSettings
+    {
+        #[settings( default = 5000_u32 )]
+        interval_ms: u32,
+    }
+
+    #[test]
+    fn derive_settings__data_attributes__overridden()
+    {
+        let mut settings = TestSettings::default();
+        settings.override_from_data_attributes( [
+            ( "data-zoomable", "false" ),
+            ( "data-caption", "Sunset" ),
+            ( "data-slideshow.interval-ms", "3000" ),
+            ( "data-slideshow.interval-ms", "soon" ),
+            ( "title", "ignored" ),
+        ] );
+
+        let mut slideshow = SettingsHashmap::new();
+        slideshow.insert( "interval_ms", SettingValue::Integer( 3000 ) );
+        let mut expected = SettingsHashmap::new();
+        expected.insert( "zoomable", SettingValue::Bool( false ) );
+        expected.insert( "caption", SettingValue::String( "Sunset".to_owned() ) );
+        expected.insert( "slideshow", SettingValue::HashMap( slideshow ) );
+
+        assert_eq!( settings.to_hashmap(), expected );
+    }
+
+    #[test]
+    fn on__several_events__called_on_each()
+    {
+        let plugin = BasePluginComponent::<TestEvent>::default();
+        let ( count, callback ) = counter();
+        plugin.on( "open close", &callback );
+
+        plugin.emit( &TestEvent::Open );
+        plugin.emit( &TestEvent::Close );
+        plugin.emit( &TestEvent::Open );
+
+        assert_eq!( count.get(), 3 );
+    }
+
+    #[test]
+    fn once__emitted_twice__called_once()
+    {
+        let plugin = BasePluginComponent::<TestEvent>::default();
+        let ( count, callback ) = counter();
+        plugin.once( "open", &callback );
+
+        plugin.emit( &TestEvent::Open );
+        plugin.emit( &TestEvent::Open );
+
+        assert_eq!( count.get(), 1 );
+    }
+
+    #[test]
+    fn off__one_event__still_called_on_others()
+    {
+        let plugin = BasePluginComponent::<TestEvent>::default();
+        let ( count, callback ) = counter();
+        plugin.on( "open close", &callback ).off( "open", &callback );
+
+        plugin.emit( &TestEvent::Open );
+        plugin.emit( &TestEvent::Close );
+
+        assert_eq!( count.get(), 1 );
+    }
 }
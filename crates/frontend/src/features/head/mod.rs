@@ -0,0 +1,524 @@
+//! Per route `<head>` management.
+//!
+//! Pages set their title, meta tags and links with the [`Head`] component or the `use_*` hooks.
+//! During server side rendering the values are collected in the [`HeadHandle`] given to
+//! [`ServerApp`]( crate::ServerApp ), and the server applies them to the `index.html` head with
+//! [`HeadData::apply_to_html`] once the page is rendered. On the client, the hooks update
+//! `document.head` and restore the previous values when the page is unmounted.
+
+use indexmap::IndexMap;
+use std::sync::{Arc, Mutex};
+use yew::{function_component, hook, html, use_context, use_effect_with_deps, AttrValue, Html, Properties};
+
+/// Element of the document head that can be set by a page.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum HeadElement
+{
+    /// `<title>`.
+    Title,
+    /// `<meta name="..." content="...">`.
+    MetaName( String ),
+    /// `<meta property="..." content="...">`, used by Open Graph.
+    MetaProperty( String ),
+    /// `<link rel="..." href="...">`.
+    Link( String ),
+}
+
+impl HeadElement
+{
+    /// Css selector of the element on the document.
+    #[must_use]
+    pub fn selector( &self ) -> String
+    {
+        match self
+        {
+            Self::Title => "title".to_owned(),
+            Self::MetaName( name ) => format!( "meta[name=\"{name}\"]" ),
+            Self::MetaProperty( property ) => format!( "meta[property=\"{property}\"]" ),
+            Self::Link( rel ) => format!( "link[rel=\"{rel}\"]" ),
+        }
+    }
+
+    /// Renders the element with `value` as its content.
+    #[must_use]
+    pub fn to_html( &self, value: &str ) -> String
+    {
+        let value = escape_html( value );
+
+        match self
+        {
+            Self::Title => format!( "<title>{value}</title>" ),
+            Self::MetaName( name ) => format!( "<meta name=\"{}\" content=\"{value}\">", escape_html( name ) ),
+            Self::MetaProperty( property ) =>
+            {
+                format!( "<meta property=\"{}\" content=\"{value}\">", escape_html( property ) )
+            }
+            Self::Link( rel ) => format!( "<link rel=\"{}\" href=\"{value}\">", escape_html( rel ) ),
+        }
+    }
+
+    /// Tag name and identifying attribute name and value of the element, if it has one.
+    fn tag_and_attr( &self ) -> ( &'static str, Option<( &'static str, &str )> )
+    {
+        match self
+        {
+            Self::Title => ( "title", None ),
+            Self::MetaName( name ) => ( "meta", Some( ( "name", name ) ) ),
+            Self::MetaProperty( property ) => ( "meta", Some( ( "property", property ) ) ),
+            Self::Link( rel ) => ( "link", Some( ( "rel", rel ) ) ),
+        }
+    }
+
+    /// Attribute holding the value of the element, `None` for the text content.
+    const fn value_attr( &self ) -> Option<&'static str>
+    {
+        match self
+        {
+            Self::Title => None,
+            Self::MetaName( _ ) | Self::MetaProperty( _ ) => Some( "content" ),
+            Self::Link( _ ) => Some( "href" ),
+        }
+    }
+}
+
+/// Head elements set while rendering, in the order they were first set.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HeadData( IndexMap<HeadElement, String> );
+
+impl HeadData
+{
+    /// Sets the value of a head element, replacing any value previously set.
+    pub fn set( &mut self, element: HeadElement, value: String ) { self.0.insert( element, value ); }
+
+    #[must_use]
+    pub fn get( &self, element: &HeadElement ) -> Option<&String> { self.0.get( element ) }
+
+    #[must_use]
+    pub fn is_empty( &self ) -> bool { self.0.is_empty() }
+
+    /// Applies the head elements to an html document ( or its beginning up to `</head>` ).
+    ///
+    /// Elements already present in the html are removed and all the elements are inserted just
+    /// before `</head>`. If there is no `</head>` the html is returned unchanged.
+    #[must_use]
+    pub fn apply_to_html( &self, html: &str ) -> String
+    {
+        if self.is_empty() || !html.contains( "</head>" )
+        {
+            return html.to_owned();
+        }
+
+        let mut html = html.to_owned();
+        let mut elements_html = String::new();
+
+        for ( element, value ) in &self.0
+        {
+            let ( tag, attr ) = element.tag_and_attr();
+
+            while let Some( range ) = find_element( &html, tag, attr )
+            {
+                html.replace_range( range, "" );
+            }
+
+            elements_html.push_str( &element.to_html( value ) );
+        }
+
+        html.replacen( "</head>", &format!( "{elements_html}</head>" ), 1 )
+    }
+}
+
+/// Shared handle used by the server to read the head elements set while rendering.
+#[derive(Clone, Debug, Default)]
+pub struct HeadHandle( Arc<Mutex<HeadData>> );
+
+impl HeadHandle
+{
+    /// Sets the value of a head element, replacing any value previously set.
+    pub fn set( &self, element: HeadElement, value: String )
+    {
+        if let Ok( mut head ) = self.0.lock()
+        {
+            head.set( element, value );
+        }
+    }
+
+    /// Gets a copy of the head elements set so far.
+    #[must_use]
+    pub fn get( &self ) -> HeadData { self.0.lock().map( |head| head.clone() ).unwrap_or_default() }
+}
+
+impl PartialEq for HeadHandle
+{
+    fn eq( &self, other: &Self ) -> bool { Arc::ptr_eq( &self.0, &other.0 ) }
+}
+
+impl Eq for HeadHandle {}
+
+/// Sets the value of a head element while the calling component is mounted.
+///
+/// Does nothing if `value` is `None`, which allows optional elements without conditional hooks.
+#[hook]
+pub fn use_head( element: HeadElement, value: Option<String> )
+{
+    // Server side: collect the value.
+    if let ( Some( handle ), Some( value ) ) = ( use_context::<HeadHandle>(), value.as_ref() )
+    {
+        handle.set( element.clone(), value.clone() );
+    }
+
+    // Client side: update the document and restore it on unmount.
+    use_effect_with_deps(
+        |( element, value )| {
+            let previous = value.as_ref().map( |value| document_head::set( element, value ) );
+            let element = element.clone();
+
+            move || {
+                if let Some( previous ) = previous
+                {
+                    document_head::restore( &element, previous );
+                }
+            }
+        },
+        ( element, value ),
+    );
+}
+
+/// Sets the document title.
+#[hook]
+pub fn use_title( title: &str ) { use_head( HeadElement::Title, Some( title.to_owned() ) ); }
+
+/// Sets a `<meta name="..." content="...">` element.
+#[hook]
+pub fn use_meta( name: &str, content: &str )
+{
+    use_head( HeadElement::MetaName( name.to_owned() ), Some( content.to_owned() ) );
+}
+
+/// Sets a `<meta property="..." content="...">` element ( e.g. Open Graph properties ).
+#[hook]
+pub fn use_meta_property( property: &str, content: &str )
+{
+    use_head( HeadElement::MetaProperty( property.to_owned() ), Some( content.to_owned() ) );
+}
+
+/// Sets the canonical link of the page.
+#[hook]
+pub fn use_canonical( href: &str ) { use_head( HeadElement::Link( "canonical".to_owned() ), Some( href.to_owned() ) ); }
+
+/// Sets the Open Graph image of the page.
+#[hook]
+pub fn use_og_image( src: &str ) { use_meta_property( "og:image", src ); }
+
+#[derive(Properties, PartialEq, Eq)]
+pub struct HeadProps
+{
+    #[prop_or_default]
+    pub title:       Option<AttrValue>,
+    #[prop_or_default]
+    pub description: Option<AttrValue>,
+    #[prop_or_default]
+    pub canonical:   Option<AttrValue>,
+    #[prop_or_default]
+    pub og_image:    Option<AttrValue>,
+}
+
+/// Sets the common head elements of a page. The title and description are also used as the Open
+/// Graph title and description.
+#[function_component( Head )]
+pub fn head( props: &HeadProps ) -> Html
+{
+    let title = props.title.as_ref().map( ToString::to_string );
+    let description = props.description.as_ref().map( ToString::to_string );
+
+    use_head( HeadElement::Title, title.clone() );
+    use_head( HeadElement::MetaProperty( "og:title".to_owned() ), title );
+    use_head( HeadElement::MetaName( "description".to_owned() ), description.clone() );
+    use_head( HeadElement::MetaProperty( "og:description".to_owned() ), description );
+    use_head(
+        HeadElement::Link( "canonical".to_owned() ),
+        props.canonical.as_ref().map( ToString::to_string ),
+    );
+    use_head(
+        HeadElement::MetaProperty( "og:image".to_owned() ),
+        props.og_image.as_ref().map( ToString::to_string ),
+    );
+
+    html! {}
+}
+
+/// Finds the byte range of the first `tag` element in `html` with the `( name, value )` attribute, or of the first
+/// one up to its closing tag if it is identified by its tag only.
+fn find_element( html: &str, tag: &str, attr: Option<( &str, &str )> ) -> Option<std::ops::Range<usize>>
+{
+    let open_tag = format!( "<{tag}" );
+    let mut offset = 0;
+
+    while let Some( start ) = html[offset..].find( &open_tag ).map( |start| start + offset )
+    {
+        let name_end = start + open_tag.len();
+        offset = name_end;
+
+        // The tag name must end there, e.g. `<metadata` isn't a `<meta`.
+        if !html[name_end..].starts_with( |c: char| c.is_ascii_whitespace() || c == '/' || c == '>' )
+        {
+            continue;
+        }
+
+        let ( attrs, attrs_len ) = parse_attributes( &html[name_end..] )?;
+        let end = name_end + attrs_len;
+
+        match attr
+        {
+            None =>
+            {
+                let close_tag = format!( "</{tag}>" );
+                return Some( start..end + html[end..].find( &close_tag )? + close_tag.len() );
+            }
+            Some( ( name, value ) ) =>
+            {
+                if attrs.iter().any( |attr| attr.0.eq_ignore_ascii_case( name ) && attr.1 == value )
+                {
+                    return Some( start..end );
+                }
+
+                offset = end;
+            }
+        }
+    }
+
+    None
+}
+
+/// Parses the attributes of an opening tag, from its name up to its `>`. Returns the attributes, with their values
+/// double, single or not quoted, and the length parsed. `None` if the tag isn't closed.
+fn parse_attributes( tag: &str ) -> Option<( Vec<( &str, &str )>, usize )>
+{
+    let bytes = tag.as_bytes();
+    let skip = |mut i: usize, is_skipped: fn( u8 ) -> bool| {
+        while bytes.get( i ).is_some_and( |byte| is_skipped( *byte ) )
+        {
+            i += 1;
+        }
+
+        i
+    };
+
+    let mut attrs = Vec::new();
+    let mut i = 0;
+
+    loop
+    {
+        // Whitespace and self closing slash between the attributes.
+        i = skip( i, |byte| byte.is_ascii_whitespace() || byte == b'/' );
+
+        if *bytes.get( i )? == b'>'
+        {
+            return Some( ( attrs, i + 1 ) );
+        }
+
+        let name_start = i;
+        i = skip( i, |byte| !byte.is_ascii_whitespace() && !matches!( byte, b'=' | b'/' | b'>' ) );
+        let name = &tag[name_start..i];
+        i = skip( i, |byte| byte.is_ascii_whitespace() );
+
+        if bytes.get( i ) != Some( &b'=' )
+        {
+            attrs.push( ( name, "" ) );
+            continue;
+        }
+
+        i = skip( i + 1, |byte| byte.is_ascii_whitespace() );
+
+        let value = if let quote @ ( b'"' | b'\'' ) = *bytes.get( i )?
+        {
+            let value_start = i + 1;
+            let value_end = value_start + tag[value_start..].find( char::from( quote ) )?;
+            i = value_end + 1;
+
+            &tag[value_start..value_end]
+        }
+        else
+        {
+            let value_start = i;
+            i = skip( i, |byte| !byte.is_ascii_whitespace() && byte != b'>' );
+
+            &tag[value_start..i]
+        };
+
+        attrs.push( ( name, value ) );
+    }
+}
+
+fn escape_html( value: &str ) -> String
+{
+    value
+        .replace( '&', "&amp;" )
+        .replace( '"', "&quot;" )
+        .replace( '<', "&lt;" )
+        .replace( '>', "&gt;" )
+}
+
+/// Updates of `document.head` on the client.
+mod document_head
+{
+    use super::HeadElement;
+    use gloo::utils::document;
+
+    /// Value of an element before it was set. `None` if the element didn't exist.
+    pub struct Previous( Option<String> );
+
+    pub fn set( element: &HeadElement, value: &str ) -> Previous
+    {
+        if *element == HeadElement::Title
+        {
+            let previous = document().title();
+            document().set_title( value );
+
+            return Previous( Some( previous ) );
+        }
+
+        let value_attr = element.value_attr().unwrap_or( "content" );
+
+        if let Ok( Some( existing ) ) = document().query_selector( &element.selector() )
+        {
+            let previous = existing.get_attribute( value_attr );
+            let _ = existing.set_attribute( value_attr, value );
+
+            return Previous( previous );
+        }
+
+        let new_element = document().create_element( element.tag_and_attr().0 );
+
+        if let ( Ok( new_element ), Some( head ) ) = ( new_element, document().head() )
+        {
+            let _ = match element
+            {
+                HeadElement::MetaName( name ) => new_element.set_attribute( "name", name ),
+                HeadElement::MetaProperty( property ) => new_element.set_attribute( "property", property ),
+                HeadElement::Link( rel ) => new_element.set_attribute( "rel", rel ),
+                HeadElement::Title => Ok( () ),
+            };
+            let _ = new_element.set_attribute( value_attr, value );
+            let _ = head.append_child( &new_element );
+        }
+
+        Previous( None )
+    }
+
+    pub fn restore( element: &HeadElement, previous: Previous )
+    {
+        if *element == HeadElement::Title
+        {
+            document().set_title( &previous.0.unwrap_or_default() );
+            return;
+        }
+
+        if let Ok( Some( existing ) ) = document().query_selector( &element.selector() )
+        {
+            match ( previous.0, element.value_attr() )
+            {
+                ( Some( previous ), Some( value_attr ) ) =>
+                {
+                    let _ = existing.set_attribute( value_attr, &previous );
+                }
+                _ => existing.remove(),
+            }
+        }
+    }
+}
+
+#[cfg( test )]
+#[allow( non_snake_case )]
+mod tests
+{
+    use super::{HeadData, HeadElement};
+
+    const INDEX_HTML_HEAD: &str = "<head>\n<meta charset=\"utf-8\" />\n<meta\n    name=\"description\"\n    \
+                                   content=\"Default.\"\n/>\n<title>Photo Story</title>\n</head><body>";
+
+    #[test]
+    fn head_data__empty__html_unchanged()
+    {
+        assert_eq!( HeadData::default().apply_to_html( INDEX_HTML_HEAD ), INDEX_HTML_HEAD );
+    }
+
+    #[test]
+    fn head_data__title_and_description__replaces_existing_elements()
+    {
+        let mut head = HeadData::default();
+        head.set( HeadElement::Title, "Album".to_owned() );
+        head.set( HeadElement::MetaName( "description".to_owned() ), "An album.".to_owned() );
+
+        assert_eq!(
+            head.apply_to_html( INDEX_HTML_HEAD ),
+            "<head>\n<meta charset=\"utf-8\" />\n\n\n<title>Album</title><meta name=\"description\" content=\"An \
+             album.\"></head><body>"
+        );
+    }
+
+    #[test]
+    fn head_data__new_element__inserted_before_head_end_and_escaped()
+    {
+        let mut head = HeadData::default();
+        head.set( HeadElement::MetaProperty( "og:image".to_owned() ), "/a.jpg?w=1&h=2".to_owned() );
+
+        assert_eq!(
+            head.apply_to_html( "<head></head>" ),
+            "<head><meta property=\"og:image\" content=\"/a.jpg?w=1&amp;h=2\"></head>"
+        );
+    }
+
+    /// Applies a new description to a head with `element`.
+    fn apply_description( element: &str ) -> String
+    {
+        let mut head = HeadData::default();
+        head.set( HeadElement::MetaName( "description".to_owned() ), "New.".to_owned() );
+
+        head.apply_to_html( &format!( "<head>{element}</head>" ) )
+    }
+
+    #[test]
+    fn head_data__longer_tag_name__not_replaced()
+    {
+        assert_eq!(
+            apply_description( "<metadata name=\"description\"></metadata>" ),
+            "<head><metadata name=\"description\"></metadata><meta name=\"description\" content=\"New.\"></head>"
+        );
+    }
+
+    #[test]
+    fn head_data__double_quoted_attributes__replaces_existing_element()
+    {
+        assert_eq!(
+            apply_description( "<meta content=\"Old > new\" name=\"description\"/>" ),
+            "<head><meta name=\"description\" content=\"New.\"></head>"
+        );
+    }
+
+    #[test]
+    fn head_data__single_quoted_attributes__replaces_existing_element()
+    {
+        assert_eq!(
+            apply_description( "<meta name='description' content='Old.'>" ),
+            "<head><meta name=\"description\" content=\"New.\"></head>"
+        );
+    }
+
+    #[test]
+    fn head_data__unquoted_attributes__replaces_existing_element()
+    {
+        assert_eq!(
+            apply_description( "<meta name=description content=Old.>" ),
+            "<head><meta name=\"description\" content=\"New.\"></head>"
+        );
+    }
+
+    #[test]
+    fn head_data__other_attribute_value__not_replaced()
+    {
+        assert_eq!(
+            apply_description( "<meta name=\"description-long\" content=\"Old.\">" ),
+            "<head><meta name=\"description-long\" content=\"Old.\"><meta name=\"description\" content=\"New.\"></head>"
+        );
+    }
+}
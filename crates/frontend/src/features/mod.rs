@@ -1 +1,3 @@
 pub mod base_component;
+pub mod head;
+pub mod response_status;
@@ -0,0 +1,88 @@
+//! Http response status of a server side rendered page.
+//!
+//! The server gives [`ServerApp`]( crate::ServerApp ) a [`ResponseStatusHandle`] that is provided as
+//! a context to the whole app. Any component of the page can then set the status ( and redirect
+//! location ) of the response with [`use_response_status`], the server answers once the page is
+//! rendered. On the client there is no handle provided and the hook does nothing.
+
+use std::sync::{Arc, Mutex};
+use yew::{hook, use_context};
+
+/// Status code and optional redirect location of a response.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResponseStatus
+{
+    pub code:     u16,
+    pub location: Option<String>,
+}
+
+impl ResponseStatus
+{
+    #[must_use]
+    pub const fn new( code: u16 ) -> Self { Self { code, location: None } }
+
+    /// 301 Moved Permanently to `location`.
+    #[must_use]
+    pub fn moved_permanently( location: &str ) -> Self
+    {
+        Self {
+            code:     301,
+            location: Some( location.to_owned() ),
+        }
+    }
+
+    /// 302 Found, temporary redirect to `location`.
+    #[must_use]
+    pub fn found( location: &str ) -> Self
+    {
+        Self {
+            code:     302,
+            location: Some( location.to_owned() ),
+        }
+    }
+
+    /// 404 Not Found.
+    #[must_use]
+    pub const fn not_found() -> Self { Self::new( 404 ) }
+
+    /// 410 Gone.
+    #[must_use]
+    pub const fn gone() -> Self { Self::new( 410 ) }
+}
+
+/// Shared handle used by the server to read the response status set while rendering.
+#[derive(Clone, Debug, Default)]
+pub struct ResponseStatusHandle( Arc<Mutex<Option<ResponseStatus>>> );
+
+impl ResponseStatusHandle
+{
+    /// Sets the response status, replacing any status previously set.
+    pub fn set( &self, status: ResponseStatus )
+    {
+        if let Ok( mut current ) = self.0.lock()
+        {
+            *current = Some( status );
+        }
+    }
+
+    /// Gets the response status, if any was set.
+    #[must_use]
+    pub fn get( &self ) -> Option<ResponseStatus> { self.0.lock().ok().and_then( |status| status.clone() ) }
+}
+
+impl PartialEq for ResponseStatusHandle
+{
+    fn eq( &self, other: &Self ) -> bool { Arc::ptr_eq( &self.0, &other.0 ) }
+}
+
+impl Eq for ResponseStatusHandle {}
+
+/// Sets the status of the server side rendered response.
+#[hook]
+pub fn use_response_status( status: ResponseStatus )
+{
+    if let Some( handle ) = use_context::<ResponseStatusHandle>()
+    {
+        handle.set( status );
+    }
+}
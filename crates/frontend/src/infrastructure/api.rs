@@ -0,0 +1,105 @@
+//! Decoding of the backend api responses, the error ones into their problem details.
+
+// The browser responses aren't `Send`, they are decoded in locally spawned futures.
+#![allow( clippy::future_not_send )]
+
+use common::dtos::errors::{ErrorCode, Problem, PROBLEM_JSON_CONTENT_TYPE};
+
+use gloo_net::http::Response;
+use serde::de::DeserializeOwned;
+use std::fmt;
+
+/// Error of an api request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiError
+{
+    /// The request couldn't be sent or its response couldn't be read.
+    Network( String ),
+    /// The api answered with an error.
+    Problem( Problem ),
+}
+
+impl ApiError
+{
+    /// Code of the error answered by the api, if it answered.
+    #[must_use]
+    pub const fn code( &self ) -> Option<ErrorCode>
+    {
+        match self
+        {
+            Self::Network( _ ) => None,
+            Self::Problem( problem ) => Some( problem.code ),
+        }
+    }
+}
+
+impl fmt::Display for ApiError
+{
+    fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+    {
+        match self
+        {
+            Self::Network( err ) => write!( f, "Network error: {err}" ),
+            Self::Problem( problem ) => problem.fmt( f ),
+        }
+    }
+}
+
+impl From<gloo_net::Error> for ApiError
+{
+    fn from( err: gloo_net::Error ) -> Self { Self::Network( err.to_string() ) }
+}
+
+/// Decodes the json body of a successful response.
+///
+/// # Errors
+///
+/// The problem details of an error response, or a network error if the body can't be read.
+pub async fn decode_json<T: DeserializeOwned>( response: Response ) -> Result<T, ApiError>
+{
+    if !response.ok()
+    {
+        return Err( ApiError::Problem( decode_problem( response ).await ) );
+    }
+
+    Ok( response.json().await? )
+}
+
+/// Decodes the text body of a successful response.
+///
+/// # Errors
+///
+/// The problem details of an error response, or a network error if the body can't be read.
+pub async fn decode_text( response: Response ) -> Result<String, ApiError>
+{
+    if !response.ok()
+    {
+        return Err( ApiError::Problem( decode_problem( response ).await ) );
+    }
+
+    Ok( response.text().await? )
+}
+
+/// Decodes the problem details of an error response. Errors not answered by the api, e.g. by a proxy, get ones
+/// built from their status.
+pub async fn decode_problem( response: Response ) -> Problem
+{
+    let is_problem = response
+        .headers()
+        .get( "content-type" )
+        .is_some_and( |content_type| content_type.starts_with( PROBLEM_JSON_CONTENT_TYPE ) );
+    let status_problem = Problem {
+        title: response.status_text(),
+        status: response.status(),
+        ..Problem::new( ErrorCode::Unknown, None )
+    };
+
+    if is_problem
+    {
+        response.json().await.unwrap_or( status_problem )
+    }
+    else
+    {
+        status_problem
+    }
+}
@@ -1,2 +1,3 @@
 // Modules.
+pub mod api;
 pub mod by_features;
@@ -12,7 +12,8 @@ use lol_alloc::{FreeListAllocator, LockedAllocator};
 static ALLOCATOR: LockedAllocator<FreeListAllocator> = LockedAllocator::new( FreeListAllocator::new() );
 
 #[macro_use]
-mod macros;
+#[doc( hidden )]
+pub mod macros;
 
 pub mod domain;
 pub mod features;
@@ -20,11 +21,19 @@ pub mod infrastructure;
 pub mod presentation;
 pub mod utils;
 
+#[cfg( feature = "ssr" )]
+pub use features::{
+    head::{HeadData, HeadHandle},
+    response_status::{ResponseStatus, ResponseStatusHandle},
+};
 use presentation::{components::lightbox, layout, routes};
 
+#[cfg( feature = "ssr" )]
 use crate::utils::unwrap_r_abort;
 use yew::prelude::*;
-use yew_router::{history, history::History, prelude::*};
+use yew_router::prelude::*;
+#[cfg( feature = "ssr" )]
+use yew_router::history::{self, History};
 
 #[function_component( Layout )]
 pub fn layout() -> Html
@@ -66,19 +75,28 @@ pub struct RequestData
 #[derive(Properties, PartialEq, Eq)]
 pub struct ServerAppProps
 {
-    pub request_data: RequestData,
+    pub request_data:    RequestData,
+    pub response_status: ResponseStatusHandle,
+    pub head:            HeadHandle,
 }
 
 #[cfg( feature = "ssr" )]
 #[function_component( ServerApp )]
 pub fn server_app( props: &ServerAppProps ) -> Html
 {
+    // Start each request from an empty lightbox, before its items and modal are created.
+    use_memo( |()| lightbox::reset_state(), () );
+
     let history = history::AnyHistory::from( history::MemoryHistory::new() );
     unwrap_r_abort( history.push_with_query( &*props.request_data.url, &props.request_data.queries ) );
 
     html! {
-        <Router history={history}>
-            <Layout />
-        </Router>
+        <ContextProvider<ResponseStatusHandle> context={props.response_status.clone()}>
+            <ContextProvider<HeadHandle> context={props.head.clone()}>
+                <Router history={history}>
+                    <Layout />
+                </Router>
+            </ContextProvider<HeadHandle>>
+        </ContextProvider<ResponseStatusHandle>>
     }
 }
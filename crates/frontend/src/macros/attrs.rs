@@ -1,28 +1,102 @@
+use indexmap::{map::Entry, IndexMap};
+use std::fmt::Display;
+use yew::AttrValue;
+
+/// Builds the attributes of an element, e.g. for `CustomChildrenContainer` or `LightboxItem`.
+///
+/// - `key` or `"key"`: boolean attribute with an empty value, hyphens are allowed, e.g. `data-src`.
+/// - `key = value`: attribute with any displayable value, e.g. a literal or an expression.
+/// - `key ?= option`: attribute only if the option is `Some`.
+/// - `key if condition`: boolean attribute only if the condition is true.
+///
+/// A later entry replaces the value of an earlier one with the same key, except for `class` which merges them.
 #[macro_export]
 macro_rules! attrs
 {
-    () => { indexmap::IndexMap::<yew::AttrValue, yew::AttrValue>::new() };
-    ( $($key:ident $(= $value:literal)?),+ ) => {{
+    // Key as a string literal.
+    ( @entries $map:ident; ) => {};
+    ( @entries $map:ident; $key:literal $($tail:tt)* ) => {
+        $crate::attrs!( @value $map; [ $key ] $($tail)* )
+    };
+    ( @entries $map:ident; $first:ident $($tail:tt)* ) => {
+        $crate::attrs!( @key $map; [ stringify!( $first ) ] $($tail)* )
+    };
 
-            let mut index_map = indexmap::IndexMap::<yew::AttrValue, yew::AttrValue>::new();
+    // Key as identifiers joined by hyphens.
+    ( @key $map:ident; [ $($key:tt)* ] - $next:ident $($tail:tt)* ) => {
+        $crate::attrs!( @key $map; [ $($key)*, "-", stringify!( $next ) ] $($tail)* )
+    };
+    ( @key $map:ident; [ $($key:tt)* ] $($tail:tt)* ) => {
+        $crate::attrs!( @value $map; [ concat!( $($key)* ) ] $($tail)* )
+    };
 
-            // Per entry repetition.
-            $(
-                let mut _value: &str = "";
+    // Value of the entry.
+    ( @value $map:ident; [ $key:expr ] ?= $value:expr $(, $($tail:tt)*)? ) => {
+        if let Some( value ) = $value
+        {
+            $crate::macros::attrs::insert_attr( &mut $map, $key, &value );
+        }
+        $crate::attrs!( @entries $map; $($($tail)*)? );
+    };
+    ( @value $map:ident; [ $key:expr ] = $value:expr $(, $($tail:tt)*)? ) => {
+        $crate::macros::attrs::insert_attr( &mut $map, $key, &$value );
+        $crate::attrs!( @entries $map; $($($tail)*)? );
+    };
+    ( @value $map:ident; [ $key:expr ] if $condition:expr $(, $($tail:tt)*)? ) => {
+        if $condition
+        {
+            $crate::macros::attrs::insert_attr( &mut $map, $key, &"" );
+        }
+        $crate::attrs!( @entries $map; $($($tail)*)? );
+    };
+    ( @value $map:ident; [ $key:expr ] $(, $($tail:tt)*)? ) => {
+        $crate::macros::attrs::insert_attr( &mut $map, $key, &"" );
+        $crate::attrs!( @entries $map; $($($tail)*)? );
+    };
+
+    () => { indexmap::IndexMap::<yew::AttrValue, yew::AttrValue>::new() };
+    ( $($entries:tt)+ ) => {{
 
-                // Optional value per entry.
-                $(
-                    _value = concat!( $value );
-                )?
+            let mut index_map = indexmap::IndexMap::<yew::AttrValue, yew::AttrValue>::new();
 
-                // Insert key and (optional) value to index map.
-                index_map.insert( stringify!($key).into(), _value.into() );
-            )*
+            $crate::attrs!( @entries index_map; $($entries)+ );
 
             index_map
     }};
 }
 
+/// Inserts an entry of `attrs!`, merging the values of `class`.
+#[doc( hidden )]
+pub fn insert_attr( attrs: &mut IndexMap<AttrValue, AttrValue>, key: &'static str, value: &dyn Display )
+{
+    let value = value.to_string();
+
+    match attrs.entry( AttrValue::from( key ) )
+    {
+        Entry::Occupied( mut entry ) if key == "class" =>
+        {
+            let classes = entry.get_mut();
+
+            if classes.is_empty()
+            {
+                *classes = value.into();
+            }
+            else if !value.is_empty()
+            {
+                *classes = format!( "{classes} {value}" ).into();
+            }
+        }
+        Entry::Occupied( mut entry ) =>
+        {
+            entry.insert( value.into() );
+        }
+        Entry::Vacant( entry ) =>
+        {
+            entry.insert( value.into() );
+        }
+    }
+}
+
 #[cfg( test )]
 #[allow( non_snake_case )]
 mod tests
@@ -72,4 +146,50 @@ mod tests
 
         assert_eq!( attrs!( key = "value", key = "value2" ), index_map );
     }
+
+    #[test]
+    fn macro_attrs__hyphenated_and_string_keys__index_map_with_joined_keys()
+    {
+        let src = String::from( "/media/1.jpg" );
+
+        let mut index_map = indexmap::IndexMap::<yew::AttrValue, yew::AttrValue>::new();
+        index_map.insert( "data-src".into(), "/media/1.jpg".into() );
+        index_map.insert( "aria-label".into(), "Photo 2".into() );
+        index_map.insert( "data-lightbox-item".into(), "".into() );
+
+        assert_eq!(
+            attrs!( data-src = src, "aria-label" = format!( "Photo {}", 1 + 1 ), data-lightbox-item, ),
+            index_map
+        );
+    }
+
+    #[test]
+    fn macro_attrs__optional_and_conditional_entries__absent_ones_omitted()
+    {
+        let title: Option<&str> = None;
+        let caption = Some( yew::AttrValue::from( "Sunset" ) );
+
+        let mut index_map = indexmap::IndexMap::<yew::AttrValue, yew::AttrValue>::new();
+        index_map.insert( "data-sub-html".into(), "Sunset".into() );
+        index_map.insert( "hidden".into(), "".into() );
+
+        assert_eq!(
+            attrs!( title ?= title, data-sub-html ?= caption, hidden if 1 < 2, disabled if 2 < 1 ),
+            index_map
+        );
+    }
+
+    #[test]
+    fn macro_attrs__multiple_classes__classes_merged()
+    {
+        let is_active = true;
+
+        let mut index_map = indexmap::IndexMap::<yew::AttrValue, yew::AttrValue>::new();
+        index_map.insert( "class".into(), "item item--active".into() );
+
+        assert_eq!(
+            attrs!( class = "item", class = "", class ?= is_active.then_some( "item--active" ) ),
+            index_map
+        );
+    }
 }
@@ -1,5 +1,7 @@
 use crate::{
-    presentation::{components::lightbox::item_view::LightboxItem, utils::attrs},
+    features::head::use_title,
+    infrastructure::api,
+    presentation::components::lightbox::item_view::LightboxItem,
     utils::unwrap_r_abort,
 };
 use gloo_net::http::Request;
@@ -14,6 +16,8 @@ pub fn component() -> Html
 #[function_component( HelloServer )]
 fn hello_server() -> Html
 {
+    use_title( "Hello server | Photo Story" );
+
     let data = use_state( || None );
     let href = use_state( || "assets/images/test.jpg" );
 
@@ -25,20 +29,7 @@ fn hello_server() -> Html
             {
                 spawn_local( async move {
                     let resp = unwrap_r_abort( Request::get( "/api/hello" ).send().await );
-                    let result = {
-                        if !resp.ok()
-                        {
-                            Err( format!(
-                                "Error fetching data {} ({})",
-                                resp.status(),
-                                resp.status_text()
-                            ) )
-                        }
-                        else
-                        {
-                            resp.text().await.map_err( |err| err.to_string() )
-                        }
-                    };
+                    let result = api::decode_text( resp ).await.map_err( |err| err.to_string() );
                     data.set( Some( result ) );
                 } );
             }
@@ -68,8 +59,8 @@ fn hello_server() -> Html
                 <>
                     <div class="bg-sky-700 px-4 py-2 text-white hover:bg-sky-800 sm:px-8 sm:py-3">{"Got server response: "}{data}</div>
 
-                    <LightboxItem data_src={href.to_string()} gallery="lightbox-test" class={classes!( "container" )}>
-                        <img src="assets/images/test.webp" alt="test img" width="500" height="400" decoding="async"/>
+                    <LightboxItem data_src={href.to_string()} gallery="lightbox-test" caption="Test image" class={classes!( "container" )}>
+                        <img src="assets/images/test.jpg" alt="test img" width="500" height="400" decoding="async"/>
                     </LightboxItem>
                     <svg xmlns="http://www.w3.org/2000/svg" width="100" height="100" viewBox="0 0 16 16"><path d="M6.12 6.75H4.87v7.05l-2.05-3-1 .71 2.67 3.93a1.29 1.29 0 0 0 1 .59 1.29 1.29 0 0 0 1-.59l2.67-3.93-1-.71-2.06 3zM9.45.59 6.78 4.52l1 .71 2.06-3v7.02h1.25v-7l2 3 1-.71L11.55.59a1.23 1.23 0 0 0-2.1 0z"/></svg>
 
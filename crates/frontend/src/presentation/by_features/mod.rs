@@ -1 +1,2 @@
 pub mod hello_server;
+pub mod not_found;
@@ -0,0 +1,24 @@
+use crate::features::{
+    head::Head,
+    response_status::{use_response_status, ResponseStatus},
+};
+use yew::{html, prelude::*};
+
+#[must_use]
+pub fn component() -> Html
+{
+    html! { <NotFound /> }
+}
+
+#[function_component( NotFound )]
+fn not_found() -> Html
+{
+    use_response_status( ResponseStatus::not_found() );
+
+    html! {
+        <>
+            <Head title="Page not found | Photo Story" />
+            <h1>{ "404" }</h1>
+        </>
+    }
+}
@@ -1,9 +1,18 @@
+#[cfg( not( feature = "ssr" ) )]
 use crate::utils::unwrap_r_abort;
+#[cfg( not( feature = "ssr" ) )]
 use gloo::utils::document;
 use indexmap::IndexMap;
-use web_sys::{Element, Node};
+use web_sys::Element;
+#[cfg( not( feature = "ssr" ) )]
+use web_sys::Node;
+#[cfg( not( feature = "ssr" ) )]
+use yew::{create_portal, html, use_memo};
+use yew::{function_component, AttrValue, Callback, Children, Html, Properties};
+#[cfg( feature = "ssr" )]
 use yew::{
-    create_portal, function_component, html, html::NodeRef, use_memo, AttrValue, Callback, Children, Html, Properties,
+    use_effect_with_deps, use_node_ref,
+    virtual_dom::{ApplyAttributeAs, Attributes, VNode, VTag},
 };
 
 #[derive(Properties, PartialEq)]
@@ -18,6 +27,47 @@ pub struct CustomChildrenContainerProps
     pub on_container_element: Callback<Element>,
 }
 
+/// With server-side rendering, the container is rendered as a real element with its children, so that the server
+/// can render it and the client adopts the pre-rendered element during hydration. The element is emitted once it is
+/// mounted.
+#[cfg( feature = "ssr" )]
+#[function_component( CustomChildrenContainer )]
+pub fn custom_children_container( props: &CustomChildrenContainerProps ) -> Html
+{
+    let node_ref = use_node_ref();
+
+    {
+        let node_ref = node_ref.clone();
+        let on_container_element = props.on_container_element.clone();
+
+        use_effect_with_deps(
+            move |_| {
+                if let Some( element ) = node_ref.cast::<Element>()
+                {
+                    on_container_element.emit( element );
+                }
+            },
+            ( props.tag.clone(), props.attrs.clone() ),
+        );
+    }
+
+    let mut container = VTag::new( props.tag.to_string() );
+    container.set_attributes( Attributes::IndexMap(
+        props
+            .attrs
+            .iter()
+            .map( |( key, value )| ( key.clone(), ( value.clone(), ApplyAttributeAs::Attribute ) ) )
+            .collect(),
+    ) );
+    container.add_children( props.children.clone() );
+    container.node_ref = node_ref;
+
+    VNode::from( container )
+}
+
+/// Without server-side rendering, the container is a detached element created before its children are rendered
+/// into it, so that it is emitted before they are mounted.
+#[cfg( not( feature = "ssr" ) )]
 #[function_component( CustomChildrenContainer )]
 pub fn custom_children_container( props: &CustomChildrenContainerProps ) -> Html
 {
@@ -25,9 +75,9 @@ pub fn custom_children_container( props: &CustomChildrenContainerProps ) -> Html
         |_| {
             let element: Element = unwrap_r_abort( document().create_element( props.tag.clone().as_str() ) );
 
-            for ( key, value ) in props.attrs.iter()
+            for ( key, value ) in &props.attrs
             {
-                element.set_attribute( key, value );
+                let _ = element.set_attribute( key, value );
             }
 
             let node: Node = element.clone().into();
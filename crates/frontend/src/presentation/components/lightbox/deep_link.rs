@@ -0,0 +1,66 @@
+//! Link of the open lightbox item in the page query, e.g. `/albums?gallery=trip&photo=/media/1.jpg`,
+//! so that it can be shared, bookmarked and rendered by the server.
+
+use super::lightbox_state::LightboxLink;
+
+/// Query keys of the gallery and of the data source of the open item.
+pub const GALLERY_QUERY_KEY: &str = "gallery";
+pub const ITEM_QUERY_KEY: &str = "photo";
+
+/// Reads the link of a page query, `None` if it doesn't have both keys.
+pub fn from_query( queries: &[( String, String )] ) -> Option<LightboxLink>
+{
+    let value = |key: &str| {
+        queries
+            .iter()
+            .find( |( query_key, _ )| query_key == key )
+            .map( |( _, value )| value.clone() )
+    };
+
+    Some( LightboxLink {
+        gallery:  value( GALLERY_QUERY_KEY )?,
+        data_src: value( ITEM_QUERY_KEY )?,
+    } )
+}
+
+/// Sets the link on a page query, or removes it if `None`, keeping the other queries.
+pub fn with_link( mut queries: Vec<( String, String )>, link: Option<&LightboxLink> ) -> Vec<( String, String )>
+{
+    queries.retain( |( key, _ )| key != GALLERY_QUERY_KEY && key != ITEM_QUERY_KEY );
+
+    if let Some( link ) = link
+    {
+        queries.push( ( GALLERY_QUERY_KEY.to_owned(), link.gallery.clone() ) );
+        queries.push( ( ITEM_QUERY_KEY.to_owned(), link.data_src.clone() ) );
+    }
+
+    queries
+}
+
+#[cfg( test )]
+#[allow( non_snake_case )]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn with_link__other_queries__kept()
+    {
+        let link = LightboxLink {
+            gallery:  "trip".to_owned(),
+            data_src: "/media/1.jpg".to_owned(),
+        };
+        let queries = vec![
+            ( "page".to_owned(), "2".to_owned() ),
+            ( GALLERY_QUERY_KEY.to_owned(), "old".to_owned() ),
+        ];
+
+        let queries = with_link( queries, Some( &link ) );
+        assert_eq!( queries.len(), 3 );
+        assert_eq!( from_query( &queries ), Some( link ) );
+
+        let queries = with_link( queries, None );
+        assert_eq!( queries, vec![( "page".to_owned(), "2".to_owned() )] );
+        assert_eq!( from_query( &queries ), None );
+    }
+}
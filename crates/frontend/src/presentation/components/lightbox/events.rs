@@ -0,0 +1,44 @@
+use crate::features::base_component::{BasePluginComponent, PluginEvent};
+
+/// Settings and event listeners of the lightbox, e.g. `plugin.on( "open change", &callback )`.
+pub type LightboxPlugin = BasePluginComponent<LightboxEvent>;
+
+/// Event emitted by the lightbox modal.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LightboxEvent
+{
+    /// The lightbox opened on an item.
+    Open
+    {
+        gallery:  String,
+        data_src: String,
+        index:    usize,
+    },
+    Close,
+    /// The lightbox moved to another item.
+    Change
+    {
+        gallery:  String,
+        data_src: String,
+        index:    usize,
+    },
+    /// The open item was zoomed in or out.
+    Zoom
+    {
+        scale: f64
+    },
+}
+
+impl PluginEvent for LightboxEvent
+{
+    fn name( &self ) -> &'static str
+    {
+        match self
+        {
+            Self::Open { .. } => "open",
+            Self::Close => "close",
+            Self::Change { .. } => "change",
+            Self::Zoom { .. } => "zoom",
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+/// Minimum distance in pixels of a swipe.
+const SWIPE_MIN_DISTANCE: f64 = 50.0;
+
+/// Maximum movement in pixels and duration in milliseconds of a tap.
+const TAP_MAX_DISTANCE: f64 = 10.0;
+const TAP_MAX_DURATION: f64 = 250.0;
+
+/// Maximum delay in milliseconds and distance in pixels between the two taps of a double tap.
+const DOUBLE_TAP_MAX_DELAY: f64 = 300.0;
+const DOUBLE_TAP_MAX_DISTANCE: f64 = 30.0;
+
+/// Gesture recognized from the pointers on the open lightbox item.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture
+{
+    None,
+    /// One pointer moved by a distance.
+    Pan( f64, f64 ),
+    /// Two pointers moved apart or together by a factor, around a focus point.
+    Pinch
+    {
+        factor: f64,
+        focus:  ( f64, f64 ),
+    },
+    SwipeLeft,
+    SwipeRight,
+    SwipeDown,
+    DoubleTap( f64, f64 ),
+}
+
+/// Point of a pointer event, with its time in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Point
+{
+    x:    f64,
+    y:    f64,
+    time: f64,
+}
+
+impl Point
+{
+    fn distance_to( &self, other: &Self ) -> f64 { ( self.x - other.x ).hypot( self.y - other.y ) }
+}
+
+/// Tracks the pointers on the open lightbox item to recognize gestures.
+#[derive(Debug, Default)]
+pub struct GestureTracker
+{
+    pointers:       HashMap<i32, Point>,
+    start:          Option<Point>,
+    last_tap:       Option<Point>,
+    is_multi_touch: bool,
+}
+
+impl GestureTracker
+{
+    pub fn is_active( &self ) -> bool { !self.pointers.is_empty() }
+
+    pub fn down( &mut self, pointer_id: i32, x: f64, y: f64, time: f64 )
+    {
+        let point = Point { x, y, time };
+
+        if self.pointers.is_empty()
+        {
+            self.start = Some( point );
+            self.is_multi_touch = false;
+        }
+        else
+        {
+            self.is_multi_touch = true;
+        }
+
+        self.pointers.insert( pointer_id, point );
+    }
+
+    pub fn move_to( &mut self, pointer_id: i32, x: f64, y: f64, time: f64 ) -> Gesture
+    {
+        let previous = match self.pointers.get( &pointer_id )
+        {
+            Some( previous ) => *previous,
+            None => return Gesture::None,
+        };
+        let point = Point { x, y, time };
+
+        let gesture = match self.other_pointer( pointer_id )
+        {
+            // Pinch between this pointer and another one.
+            Some( other ) if self.pointers.len() == 2 =>
+            {
+                let previous_distance = previous.distance_to( &other );
+
+                if previous_distance > 0.0
+                {
+                    Gesture::Pinch {
+                        factor: point.distance_to( &other ) / previous_distance,
+                        focus:  ( f64::midpoint( point.x, other.x ), f64::midpoint( point.y, other.y ) ),
+                    }
+                }
+                else
+                {
+                    Gesture::None
+                }
+            }
+            Some( _ ) => Gesture::None,
+            None => Gesture::Pan( point.x - previous.x, point.y - previous.y ),
+        };
+
+        self.pointers.insert( pointer_id, point );
+
+        gesture
+    }
+
+    /// Releases a pointer. Swipes are only recognized when the item is not zoomed, as moving a zoomed
+    /// item pans it.
+    pub fn up( &mut self, pointer_id: i32, x: f64, y: f64, time: f64, is_zoomed: bool ) -> Gesture
+    {
+        if self.pointers.remove( &pointer_id ).is_none() || !self.pointers.is_empty()
+        {
+            return Gesture::None;
+        }
+
+        let start = match self.start.take()
+        {
+            Some( start ) if !self.is_multi_touch => start,
+            _ => return Gesture::None,
+        };
+        let end = Point { x, y, time };
+        let ( dx, dy ) = ( end.x - start.x, end.y - start.y );
+
+        if start.distance_to( &end ) <= TAP_MAX_DISTANCE && end.time - start.time <= TAP_MAX_DURATION
+        {
+            let is_double_tap = self.last_tap.take().is_some_and( |last_tap| {
+                end.time - last_tap.time <= DOUBLE_TAP_MAX_DELAY
+                    && end.distance_to( &last_tap ) <= DOUBLE_TAP_MAX_DISTANCE
+            } );
+
+            if is_double_tap
+            {
+                return Gesture::DoubleTap( end.x, end.y );
+            }
+
+            self.last_tap = Some( end );
+            return Gesture::None;
+        }
+
+        if is_zoomed
+        {
+            Gesture::None
+        }
+        else if dx.abs() >= SWIPE_MIN_DISTANCE && dx.abs() > dy.abs()
+        {
+            if dx < 0.0 { Gesture::SwipeLeft } else { Gesture::SwipeRight }
+        }
+        else if dy >= SWIPE_MIN_DISTANCE && dy > dx.abs()
+        {
+            Gesture::SwipeDown
+        }
+        else
+        {
+            Gesture::None
+        }
+    }
+
+    /// Forgets a pointer without recognizing a gesture, e.g. when the browser cancels it.
+    pub fn cancel( &mut self, pointer_id: i32 )
+    {
+        self.pointers.remove( &pointer_id );
+
+        if self.pointers.is_empty()
+        {
+            self.start = None;
+        }
+    }
+
+    fn other_pointer( &self, pointer_id: i32 ) -> Option<Point>
+    {
+        self.pointers
+            .iter()
+            .find( |( id, _ )| **id != pointer_id )
+            .map( |( _, point )| *point )
+    }
+}
+
+#[cfg( test )]
+#[allow( non_snake_case )]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn up__horizontal_move__swipe()
+    {
+        let mut tracker = GestureTracker::default();
+        tracker.down( 1, 300.0, 100.0, 0.0 );
+        tracker.move_to( 1, 200.0, 110.0, 50.0 );
+
+        assert_eq!( tracker.up( 1, 150.0, 110.0, 100.0, false ), Gesture::SwipeLeft );
+    }
+
+    #[test]
+    fn up__zoomed__no_swipe()
+    {
+        let mut tracker = GestureTracker::default();
+        tracker.down( 1, 300.0, 100.0, 0.0 );
+
+        assert_eq!( tracker.up( 1, 150.0, 100.0, 100.0, true ), Gesture::None );
+    }
+
+    #[test]
+    fn up__two_quick_taps__double_tap()
+    {
+        let mut tracker = GestureTracker::default();
+        tracker.down( 1, 100.0, 100.0, 0.0 );
+        assert_eq!( tracker.up( 1, 100.0, 100.0, 50.0, false ), Gesture::None );
+
+        tracker.down( 1, 102.0, 101.0, 200.0 );
+        assert_eq!( tracker.up( 1, 102.0, 101.0, 250.0, false ), Gesture::DoubleTap( 102.0, 101.0 ) );
+    }
+
+    #[test]
+    fn move_to__two_pointers__pinch()
+    {
+        let mut tracker = GestureTracker::default();
+        tracker.down( 1, 100.0, 100.0, 0.0 );
+        tracker.down( 2, 200.0, 100.0, 0.0 );
+
+        assert_eq!(
+            tracker.move_to( 2, 300.0, 100.0, 10.0 ),
+            Gesture::Pinch {
+                factor: 2.0,
+                focus:  ( 200.0, 100.0 ),
+            }
+        );
+        assert_eq!( tracker.up( 2, 300.0, 100.0, 20.0, false ), Gesture::None );
+        assert_eq!( tracker.up( 1, 100.0, 100.0, 30.0, false ), Gesture::None );
+    }
+}
@@ -0,0 +1,87 @@
+use common::dtos::photos::PhotoMetadataResponse;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct LightboxInfoPanelProps
+{
+    pub metadata: PhotoMetadataResponse, // camera metadata of the open lightbox item.
+}
+
+/// Panel of the lightbox modal listing the camera metadata of the open item.
+#[function_component( LightboxInfoPanel )]
+pub fn lightbox_info_panel( props: &LightboxInfoPanelProps ) -> Html
+{
+    let metadata = &props.metadata;
+
+    let camera = match ( metadata.camera_make.as_deref(), metadata.camera_model.as_deref() )
+    {
+        // Models often already start with the make, e.g. "Canon" and "Canon EOS R5".
+        ( Some( make ), Some( model ) ) if model.starts_with( make ) => Some( model.to_owned() ),
+        ( Some( make ), Some( model ) ) => Some( format!( "{make} {model}" ) ),
+        ( make, model ) => make.or( model ).map( ToOwned::to_owned ),
+    };
+
+    let exposure = [
+        metadata.focal_length_mm.map( |focal_length| format!( "{focal_length:.0} mm" ) ),
+        metadata.f_number.map( |f_number| format!( "f/{f_number:.1}" ) ),
+        metadata.exposure_time_secs.map( format_exposure_time ),
+        metadata.iso.map( |iso| format!( "ISO {iso}" ) ),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+
+    let rows = [
+        ( "Camera", camera ),
+        ( "Lens", metadata.lens_model.clone() ),
+        ( "Exposure", ( !exposure.is_empty() ).then( || exposure.join( " · " ) ) ),
+        (
+            "Taken",
+            metadata.taken_at.map( |taken_at| taken_at.format( "%Y-%m-%d %H:%M UTC" ).to_string() ),
+        ),
+        (
+            "Location",
+            metadata.gps.map( |gps| format!( "{:.5}, {:.5}", gps.latitude, gps.longitude ) ),
+        ),
+        ( "Rating", metadata.rating.map( |rating| "★".repeat( usize::from( rating.min( 5 ) ) ) ) ),
+    ];
+
+    let rows = rows
+        .into_iter()
+        .filter_map( |( label, value )| value.map( |value| ( label, value ) ) )
+        .map( |( label, value )| {
+            html! {
+                <>
+                    <dt class="lightbox-info-panel__label">{label}</dt>
+                    <dd class="lightbox-info-panel__value">{value}</dd>
+                </>
+            }
+        } )
+        .collect::<Vec<_>>();
+
+    html! {
+        <aside class="lightbox-info-panel" aria-label="Photo information">
+            if rows.is_empty()
+            {
+                <p class="lightbox-info-panel__empty">{"No camera information."}</p>
+            }
+            else
+            {
+                <dl class="lightbox-info-panel__list">{for rows}</dl>
+            }
+        </aside>
+    }
+}
+
+/// Formats an exposure time as photographers do, e.g. `1/250 s` or `2 s`.
+fn format_exposure_time( exposure_time_secs: f64 ) -> String
+{
+    if exposure_time_secs > 0.0 && exposure_time_secs < 1.0
+    {
+        format!( "1/{:.0} s", 1.0 / exposure_time_secs )
+    }
+    else
+    {
+        format!( "{exposure_time_secs} s" )
+    }
+}
@@ -1,22 +1,12 @@
-use gloo::utils::document;
+use common::dtos::photos::PhotoMetadataResponse;
 use indexmap::IndexMap;
-use std::{ops::Deref, rc::Rc};
-use web_sys::*;
-use yew::{
-    html::IntoPropValue,
-    prelude::*,
-    virtual_dom::{ApplyAttributeAs, Attributes, VNode},
-};
-
-use super::{lightbox_state::LightboxState, modal_view::LightboxModal};
-
-use crate::{
-    features::base_component::{BasePluginComponent, SettingValue, Settings},
-    presentation::components::custom_children_container::CustomChildrenContainer,
-    utils::{unwrap_abort, unwrap_r_abort},
-};
-use gloo::events::EventListener;
-use gloo_console::info;
+use std::rc::Rc;
+use web_sys::{HtmlElement, MouseEvent};
+use yew::prelude::*;
+
+use super::lightbox_state::{LightboxEntry, LightboxState, MAX_ZOOM_SCALE};
+
+use crate::features::base_component::Settings;
 use yew::html::Scope;
 use yewdux::prelude::Dispatch;
 
@@ -45,6 +35,20 @@ pub struct LightboxItemProps
     pub gallery:  AttrValue, // name of the gallery in which the lightbox item is included.
     #[prop_or_default]
     pub caption:  Option<AttrValue>, // caption for the lightbox item.
+    #[prop_or_default]
+    pub metadata: Option<PhotoMetadataResponse>, // camera metadata shown on the lightbox info panel.
+    #[prop_or_default]
+    pub settings: LightboxItemSettings, // overridden by the `data-*` entries of `attrs`.
+}
+
+/// Settings of a lightbox item, e.g. `data-zoomable="false"` or `data-max-zoom-scale="2"` on its attrs.
+#[derive(Debug, Clone, PartialEq, Settings)]
+pub struct LightboxItemSettings
+{
+    #[settings( rename = "zoomable", default = true )]
+    pub is_zoomable:    bool,
+    #[settings( default = MAX_ZOOM_SCALE )]
+    pub max_zoom_scale: f64,
 }
 
 pub enum LightboxItemMsg
@@ -72,29 +76,34 @@ impl Component for LightboxItem
         let link = ctx.link();
         let dispatch = Dispatch::<LightboxState>::subscribe( link.callback( LightboxItemMsg::State ) );
 
-        // Add the data source to the corresponding gallery on the lightbox state.
-        dispatch.reduce_mut( |state| state.add_data_source( &props.gallery, &props.data_src ) );
+        let node_ref = NodeRef::default();
+
+        // Add the item to the corresponding gallery on the lightbox state.
+        dispatch.reduce_mut( |state| state.add_item( &props.gallery, Self::entry( props, &node_ref ) ) );
 
         Self {
             state: dispatch.get(),
             dispatch,
-            node_ref: NodeRef::default(),
+            node_ref,
         }
     }
 
-    fn update( &mut self, _ctx: &Context<Self>, msg: Self::Message ) -> bool
+    fn update( &mut self, ctx: &Context<Self>, msg: Self::Message ) -> bool
     {
         match msg
         {
             LightboxItemMsg::State( state ) =>
             {
+                // The item doesn't render anything from the state.
                 self.state = state;
-                true
+                false
             }
             LightboxItemMsg::DontUpdate => false,
             LightboxItemMsg::OpenLightbox =>
             {
-                self.dispatch.reduce_mut( |state| state.is_open = true );
+                let props = ctx.props();
+
+                self.dispatch.reduce_mut( |state| state.open( &props.gallery, &props.data_src ) );
                 false
             }
         }
@@ -103,30 +112,23 @@ impl Component for LightboxItem
     fn changed( &mut self, ctx: &Context<Self>, old_props: &Self::Properties ) -> bool
     {
         let props = ctx.props();
+        let mut is_moved = false;
 
-        if props.gallery != old_props.gallery || props.data_src != old_props.data_src
-        {
-            // Update the data source to the corresponding gallery on the lightbox state.
-            self.dispatch.reduce_mut( |state| {
-                state.remove_data_source( &old_props.gallery, &old_props.data_src );
-                state.remove_gallery_if_empty( &old_props.gallery );
-                state.add_data_source( &props.gallery, &props.data_src );
-            } );
+        self.dispatch.reduce_mut( |state| {
+            is_moved = Self::update_state( state, old_props, props, &self.node_ref );
+        } );
 
-            true
-        }
-        else
-        {
-            false
-        }
+        is_moved
     }
 
+    // The dynamic tag expands to a match over `()` in the html macro.
+    #[allow( clippy::ignored_unit_patterns )]
     fn view( &self, ctx: &Context<Self> ) -> Html
     {
         let props = ctx.props();
         let link = ctx.link();
 
-        let onclick = LightboxItem::on_click( &link );
+        let onclick = Self::on_click( link );
 
         html! {
             <>
@@ -144,10 +146,16 @@ impl Component for LightboxItem
         }
     }
 
-    fn rendered( &mut self, ctx: &Context<Self>, _first_render: bool )
+    fn rendered( &mut self, ctx: &Context<Self>, first_render: bool )
     {
         let props = ctx.props();
 
+        // Items are added as they are created, which may not be the order of their elements.
+        if first_render
+        {
+            self.dispatch.reduce_mut( |state| state.sort_gallery( &props.gallery ) );
+        }
+
         // Set attrs props to node_ref.
         if let Some( element ) = self.node_ref.cast::<HtmlElement>()
         {
@@ -156,7 +164,7 @@ impl Component for LightboxItem
             // Check attrs property.
             if let Some( attrs ) = props.attrs.as_ref()
             {
-                for ( key, value ) in attrs.iter()
+                for ( key, value ) in attrs
                 {
                     // Check if href is set.
                     if key.as_str() == "href"
@@ -164,22 +172,91 @@ impl Component for LightboxItem
                         has_href = true;
                     }
 
-                    element.set_attribute( key, value );
+                    let _ = element.set_attribute( key, value );
                 }
             }
 
             // If tag is "a" and href is not set, set it to data_src.
             if props.tag.as_str() == "a" && !has_href
             {
-                element.set_attribute( "href", &props.data_src );
+                let _ = element.set_attribute( "href", &props.data_src );
             }
         }
     }
+
+    fn destroy( &mut self, ctx: &Context<Self> )
+    {
+        let props = ctx.props();
+
+        // Remove the item from its gallery, which closes the lightbox if it is open on it.
+        self.dispatch.reduce_mut( |state| state.remove_item( &props.gallery, &props.data_src ) );
+    }
 }
 
 impl LightboxItem
 {
-    fn on_click( link: &Scope<LightboxItem> ) -> Callback<MouseEvent>
+    /// Updates the item on the lightbox state for new props, returns whether it moved to another
+    /// gallery or data source.
+    fn update_state(
+        state: &mut LightboxState,
+        old_props: &LightboxItemProps,
+        props: &LightboxItemProps,
+        node_ref: &NodeRef,
+    ) -> bool
+    {
+        if props.gallery != old_props.gallery
+        {
+            // Move the item to the corresponding gallery.
+            state.remove_item( &old_props.gallery, &old_props.data_src );
+            state.add_item( &props.gallery, Self::entry( props, node_ref ) );
+            state.sort_gallery( &props.gallery );
+
+            true
+        }
+        else if props.data_src != old_props.data_src
+        {
+            // Rename the item, keeping its position in the gallery.
+            state.replace_item( &props.gallery, &old_props.data_src, Self::entry( props, node_ref ) );
+
+            true
+        }
+        else if props.caption != old_props.caption
+            || props.metadata != old_props.metadata
+            || props.settings != old_props.settings
+            || props.attrs != old_props.attrs
+        {
+            // Update the item in place.
+            state.add_item( &props.gallery, Self::entry( props, node_ref ) );
+
+            false
+        }
+        else
+        {
+            false
+        }
+    }
+
+    fn entry( props: &LightboxItemProps, node_ref: &NodeRef ) -> LightboxEntry
+    {
+        let mut settings = props.settings.clone();
+
+        if let Some( attrs ) = props.attrs.as_ref()
+        {
+            settings.override_from_data_attributes(
+                attrs.iter().map( |( key, value )| ( key.as_str(), value.as_str() ) ),
+            );
+        }
+
+        LightboxEntry {
+            data_src: props.data_src.to_string(),
+            caption:  props.caption.as_ref().map( ToString::to_string ),
+            metadata: props.metadata.clone(),
+            node_ref: node_ref.clone(),
+            settings,
+        }
+    }
+
+    fn on_click( link: &Scope<Self> ) -> Callback<MouseEvent>
     {
         link.callback( |event: MouseEvent| {
             // Allow disabling default behaviour.
@@ -201,3 +278,156 @@ impl LightboxItem
         } )
     }
 }
+
+#[cfg( test )]
+#[allow( non_snake_case )]
+mod tests
+{
+    use super::*;
+
+    fn props( gallery: &'static str, data_src: &'static str ) -> LightboxItemProps
+    {
+        LightboxItemProps {
+            tag:      AttrValue::from( "a" ),
+            id:       None,
+            class:    None,
+            style:    None,
+            attrs:    None,
+            children: Children::default(),
+            data_src: AttrValue::from( data_src ),
+            gallery:  AttrValue::from( gallery ),
+            caption:  None,
+            metadata: None,
+            settings: LightboxItemSettings::default(),
+        }
+    }
+
+    /// State with one item per data source on a gallery, as added by `LightboxItem::create`.
+    fn state( gallery: &'static str, data_srcs: &[&'static str] ) -> LightboxState
+    {
+        let mut state = LightboxState::default();
+
+        for data_src in data_srcs
+        {
+            let props = props( gallery, data_src );
+            state.add_item( &props.gallery, LightboxItem::entry( &props, &NodeRef::default() ) );
+        }
+
+        state
+    }
+
+    fn update( state: &mut LightboxState, old_props: &LightboxItemProps, props: &LightboxItemProps ) -> bool
+    {
+        LightboxItem::update_state( state, old_props, props, &NodeRef::default() )
+    }
+
+    fn data_srcs( state: &LightboxState, gallery: &str ) -> Vec<String>
+    {
+        state
+            .gallery( gallery )
+            .map( |items| items.iter().map( |item| item.data_src.clone() ).collect() )
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn update_state__data_src_renamed__position_kept()
+    {
+        let mut state = state( "gallery", &["a.jpg", "b.jpg", "c.jpg"] );
+
+        let is_moved = update( &mut state, &props( "gallery", "b.jpg" ), &props( "gallery", "d.jpg" ) );
+
+        assert!( is_moved );
+        assert_eq!( data_srcs( &state, "gallery" ), vec!["a.jpg", "d.jpg", "c.jpg"] );
+    }
+
+    #[test]
+    fn update_state__open_item_renamed__stays_open()
+    {
+        let mut state = state( "gallery", &["a.jpg", "b.jpg"] );
+        state.open( &AttrValue::from( "gallery" ), &AttrValue::from( "b.jpg" ) );
+
+        update( &mut state, &props( "gallery", "b.jpg" ), &props( "gallery", "c.jpg" ) );
+
+        assert!( state.is_open );
+        assert_eq!( state.open_item().map( |item| item.data_src ), Some( "c.jpg".to_owned() ) );
+    }
+
+    #[test]
+    fn update_state__gallery_changed__moved_and_empty_gallery_removed()
+    {
+        let mut state = state( "old", &["a.jpg"] );
+        let previous_state = state.clone();
+
+        let is_moved = update( &mut state, &props( "old", "a.jpg" ), &props( "new", "a.jpg" ) );
+
+        assert!( is_moved );
+        assert!( !state.has_gallery( &AttrValue::from( "old" ) ) );
+        assert_eq!( data_srcs( &state, "new" ), vec!["a.jpg"] );
+        assert!( previous_state != state );
+    }
+
+    #[test]
+    fn update_state__caption_changed__updated_in_place()
+    {
+        let mut state = state( "gallery", &["a.jpg", "b.jpg"] );
+        let previous_state = state.clone();
+        let new_props = LightboxItemProps {
+            caption: Some( AttrValue::from( "Sunset" ) ),
+            ..props( "gallery", "a.jpg" )
+        };
+
+        let is_moved = update( &mut state, &props( "gallery", "a.jpg" ), &new_props );
+
+        assert!( !is_moved );
+        assert_eq!( data_srcs( &state, "gallery" ), vec!["a.jpg", "b.jpg"] );
+        assert_eq!( state.gallery( "gallery" ).unwrap()[0].caption.as_deref(), Some( "Sunset" ) );
+        assert!( previous_state != state );
+    }
+
+    #[test]
+    fn update_state__same_props__state_unchanged()
+    {
+        let mut state = state( "gallery", &["a.jpg"] );
+        let previous_state = state.clone();
+
+        let is_moved = update( &mut state, &props( "gallery", "a.jpg" ), &props( "gallery", "a.jpg" ) );
+
+        assert!( !is_moved );
+        assert!( previous_state == state );
+    }
+
+    #[test]
+    fn entry__data_attributes__override_settings()
+    {
+        let props = LightboxItemProps {
+            attrs: Some( attrs!( data-zoomable = false, data-max-zoom-scale = 2, title = "Sunset" ) ),
+            ..props( "gallery", "a.jpg" )
+        };
+
+        let settings = LightboxItem::entry( &props, &NodeRef::default() ).settings;
+
+        assert_eq!(
+            settings,
+            LightboxItemSettings {
+                is_zoomable:    false,
+                max_zoom_scale: 2.0,
+            }
+        );
+    }
+
+    #[test]
+    fn remove_item__open_item__closed()
+    {
+        let mut state = state( "gallery", &["a.jpg", "b.jpg", "c.jpg"] );
+        state.open( &AttrValue::from( "gallery" ), &AttrValue::from( "c.jpg" ) );
+
+        // Removing another item keeps the open one.
+        state.remove_item( &AttrValue::from( "gallery" ), &AttrValue::from( "a.jpg" ) );
+        assert_eq!( state.open_index, 1 );
+        assert_eq!( state.open_item().map( |item| item.data_src ), Some( "c.jpg".to_owned() ) );
+
+        state.remove_item( &AttrValue::from( "gallery" ), &AttrValue::from( "c.jpg" ) );
+        assert!( !state.is_open );
+        assert_eq!( data_srcs( &state, "gallery" ), vec!["b.jpg"] );
+    }
+}
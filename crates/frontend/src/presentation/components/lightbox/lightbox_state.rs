@@ -1,65 +1,466 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use common::dtos::photos::PhotoMetadataResponse;
+use std::{cmp::Ordering, collections::HashMap, rc::Rc};
+use web_sys::Node;
 use yew::{AttrValue, NodeRef};
 use yewdux::prelude::Store;
 
-#[derive(Default, Clone, PartialEq, Eq, Store)]
+use super::item_view::LightboxItemSettings;
+
+/// Item of a lightbox gallery, registered by a `LightboxItem`.
+#[derive(Clone, PartialEq)]
+pub struct LightboxEntry
+{
+    pub data_src: String,
+    pub caption:  Option<String>,
+    pub metadata: Option<PhotoMetadataResponse>,
+    pub node_ref: NodeRef, // element of the item, focused again when the lightbox is closed on it.
+    pub settings: LightboxItemSettings,
+}
+
+/// Item of a lightbox gallery as kept in the page url, see `deep_link`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LightboxLink
+{
+    pub gallery:  String,
+    pub data_src: String,
+}
+
+/// Maximum zoom scale of the open item.
+pub const MAX_ZOOM_SCALE: f64 = 4.0;
+
+/// Zoom and pan of the open item, relative to its fitted size and centered position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightboxZoom
+{
+    pub scale:    f64,
+    pub offset_x: f64,
+    pub offset_y: f64,
+}
+
+impl Default for LightboxZoom
+{
+    fn default() -> Self
+    {
+        Self {
+            scale:    1.0,
+            offset_x: 0.0,
+            offset_y: 0.0,
+        }
+    }
+}
+
+impl LightboxZoom
+{
+    pub fn is_zoomed( &self ) -> bool { self.scale > 1.0 }
+
+    /// Scales by a factor keeping the focus point still, up to a maximum scale. The focus point and the
+    /// bounds are relative to the center and size of the fitted item.
+    pub fn zoom_at( &mut self, factor: f64, focus: ( f64, f64 ), bounds: ( f64, f64 ), max_scale: f64 )
+    {
+        let scale = ( self.scale * factor ).clamp( 1.0, max_scale.max( 1.0 ) );
+        let ratio = scale / self.scale;
+
+        self.offset_x = ( focus.0 - self.offset_x ).mul_add( -ratio, focus.0 );
+        self.offset_y = ( focus.1 - self.offset_y ).mul_add( -ratio, focus.1 );
+        self.scale = scale;
+        self.clamp( bounds );
+    }
+
+    /// Moves by a distance, without showing anything outside of the item.
+    pub fn pan_by( &mut self, delta: ( f64, f64 ), bounds: ( f64, f64 ) )
+    {
+        self.offset_x += delta.0;
+        self.offset_y += delta.1;
+        self.clamp( bounds );
+    }
+
+    /// Keeps the scaled item covering its fitted area.
+    fn clamp( &mut self, bounds: ( f64, f64 ) )
+    {
+        let max_offset_x = ( self.scale - 1.0 ) * bounds.0 / 2.0;
+        let max_offset_y = ( self.scale - 1.0 ) * bounds.1 / 2.0;
+
+        self.offset_x = self.offset_x.clamp( -max_offset_x, max_offset_x );
+        self.offset_y = self.offset_y.clamp( -max_offset_y, max_offset_y );
+    }
+}
+
+/// Galleries of lightbox items, in the order of their elements on the page.
+///
+/// The galleries are never mutated in place: changing them replaces the map, so that the previous
+/// state held by subscribers still compares as different and they are notified.
+type Galleries = Rc<HashMap<String, Rc<Vec<LightboxEntry>>>>;
+
+#[derive(Default, Clone, PartialEq, Store)]
 pub struct LightboxState
 {
-    pub is_open:                 bool,
-    pub data_sources_by_gallery: Rc<RefCell<HashMap<String, Vec<String>>>>,
+    pub is_open:      bool,
+    pub open_gallery: Option<String>,
+    pub open_index:   usize,
+    pub zoom:         LightboxZoom,
+    pub pending_link: Option<LightboxLink>, // link to open once its item is added.
+    galleries:        Galleries,
 }
 
 impl LightboxState
 {
-    pub fn add_data_source( &mut self, gallery: &AttrValue, data_src: &AttrValue )
+    /// Items of a gallery, in page order.
+    pub fn gallery( &self, gallery: &str ) -> Option<&[LightboxEntry]>
+    {
+        self.galleries.get( gallery ).map( |items| items.as_slice() )
+    }
+
+    pub fn has_gallery( &self, gallery: &AttrValue ) -> bool { self.galleries.contains_key( gallery.as_str() ) }
+
+    /// Adds an item to a gallery, or updates it if its data source is already there.
+    pub fn add_item( &mut self, gallery: &AttrValue, entry: LightboxEntry )
+    {
+        let is_pending = self
+            .pending_link
+            .as_ref()
+            .is_some_and( |link| link.gallery == gallery.as_str() && link.data_src == entry.data_src );
+        let data_src = AttrValue::from( entry.data_src.clone() );
+
+        self.update_gallery( gallery, |items| {
+            match items.iter_mut().find( |item| item.data_src == entry.data_src )
+            {
+                Some( item ) => *item = entry,
+                None => items.push( entry ),
+            }
+        } );
+
+        if is_pending
+        {
+            self.open( gallery, &data_src );
+        }
+    }
+
+    /// Replaces the item of a gallery with the given data source, keeping its position. Adds the new
+    /// item if the old one isn't there.
+    pub fn replace_item( &mut self, gallery: &AttrValue, old_data_src: &AttrValue, entry: LightboxEntry )
+    {
+        let is_replaced = self
+            .gallery( gallery )
+            .is_some_and( |items| items.iter().any( |item| item.data_src == old_data_src.as_str() ) );
+
+        if !is_replaced
+        {
+            self.add_item( gallery, entry );
+            return;
+        }
+
+        // Follow the open item if it is the one replaced.
+        let is_open_item = self.open_gallery.as_deref() == Some( gallery.as_str() )
+            && self.open_item().is_some_and( |item| item.data_src == old_data_src.as_str() );
+        let data_src = entry.data_src.clone();
+
+        self.update_gallery( gallery, |items| {
+            // Drop any other item already using the new data source.
+            items.retain( |item| item.data_src == old_data_src.as_str() || item.data_src != entry.data_src );
+
+            if let Some( item ) = items.iter_mut().find( |item| item.data_src == old_data_src.as_str() )
+            {
+                *item = entry;
+            }
+        } );
+
+        if is_open_item
+        {
+            if let Some( index ) = self.index_of( gallery, &data_src )
+            {
+                self.open_index = index;
+            }
+        }
+    }
+
+    /// Removes an item from a gallery, and the gallery if it is left empty. Closes the lightbox if it
+    /// was open on the item.
+    pub fn remove_item( &mut self, gallery: &AttrValue, data_src: &AttrValue )
+    {
+        if self.index_of( gallery, data_src ).is_none()
+        {
+            return;
+        }
+
+        let is_open_item = self.open_gallery.as_deref() == Some( gallery.as_str() )
+            && self.open_item().is_some_and( |item| item.data_src == data_src.as_str() );
+
+        self.update_gallery( gallery, |items| items.retain( |item| item.data_src != data_src.as_str() ) );
+
+        if is_open_item
+        {
+            self.close();
+        }
+    }
+
+    /// Sorts the items of a gallery in the order of their elements on the page. Items that are not
+    /// mounted keep their order, after the mounted ones.
+    pub fn sort_gallery( &mut self, gallery: &AttrValue )
+    {
+        let is_unsorted = self
+            .gallery( gallery )
+            .is_some_and( |items| items.windows( 2 ).any( |pair| page_order( &pair[0], &pair[1] ).is_gt() ) );
+
+        if is_unsorted
+        {
+            self.update_gallery( gallery, |items| items.sort_by( page_order ) );
+        }
+    }
+
+    /// Opens the lightbox on the item of a gallery with the given data source.
+    pub fn open( &mut self, gallery: &AttrValue, data_src: &AttrValue )
+    {
+        if let Some( index ) = self.index_of( gallery, data_src )
+        {
+            self.is_open = true;
+            self.open_gallery = Some( gallery.to_string() );
+            self.open_index = index;
+            self.zoom = LightboxZoom::default();
+            self.pending_link = None;
+        }
+    }
+
+    pub fn close( &mut self )
     {
-        let gallery = gallery.as_str();
-        let data_src = data_src.as_str();
+        self.is_open = false;
+        self.zoom = LightboxZoom::default();
+        self.pending_link = None;
+    }
 
-        let mut data_sources_by_gallery = self.data_sources_by_gallery.borrow_mut();
-        let data_sources = data_sources_by_gallery
-            .entry( gallery.to_string() )
-            .or_insert( Vec::new() );
+    /// Opens the item of a link, or waits for the item to be added if it isn't yet. Closes the lightbox
+    /// if there is no link.
+    pub fn follow_link( &mut self, link: Option<LightboxLink> )
+    {
+        if link == self.link()
+        {
+            return;
+        }
 
-        if !data_sources.contains( &data_src.to_string() )
+        match link
         {
-            data_sources.push( data_src.to_string() );
+            Some( link ) =>
+            {
+                self.close();
+                self.open( &AttrValue::from( link.gallery.clone() ), &AttrValue::from( link.data_src.clone() ) );
+
+                if !self.is_open
+                {
+                    self.pending_link = Some( link );
+                }
+            }
+            None => self.close(),
         }
     }
 
-    pub fn remove_data_source( &mut self, gallery: &AttrValue, data_src: &AttrValue )
+    /// Items next to the open one in its gallery, wrapping around, e.g. to preload them.
+    pub fn adjacent_items( &self ) -> Vec<LightboxEntry>
     {
-        let gallery = gallery.as_str();
-        let data_src = data_src.as_str();
+        let len = self.open_gallery_len();
+
+        if !self.is_open || len < 2
+        {
+            return Vec::new();
+        }
+
+        let mut indexes = vec![( self.open_index + 1 ) % len, ( self.open_index + len - 1 ) % len];
+        indexes.dedup();
 
-        let mut data_sources_by_gallery = self.data_sources_by_gallery.borrow_mut();
+        self.open_gallery
+            .as_ref()
+            .and_then( |gallery| self.gallery( gallery ) )
+            .map( |items| indexes.into_iter().filter_map( |index| items.get( index ).cloned() ).collect() )
+            .unwrap_or_default()
+    }
 
-        if let Some( data_sources ) = data_sources_by_gallery.get_mut( gallery )
+    /// Link of the open item, or of the item waiting to be opened.
+    pub fn link( &self ) -> Option<LightboxLink>
+    {
+        match ( self.open_gallery.as_ref(), self.open_item() )
         {
-            data_sources.retain( |data_source| data_source != data_src );
+            ( Some( gallery ), Some( item ) ) => Some( LightboxLink {
+                gallery:  gallery.clone(),
+                data_src: item.data_src,
+            } ),
+            _ => self.pending_link.clone(),
         }
     }
 
-    pub fn has_gallery( &self, gallery: &AttrValue ) -> bool
+    /// Moves to the next item of the open gallery, wrapping around to the first one.
+    pub fn next( &mut self )
     {
-        let gallery = gallery.as_str();
+        let len = self.open_gallery_len();
 
-        let data_sources_by_gallery = self.data_sources_by_gallery.borrow();
-        data_sources_by_gallery.contains_key( gallery )
+        if len > 0
+        {
+            self.open_index = ( self.open_index + 1 ) % len;
+            self.zoom = LightboxZoom::default();
+        }
     }
 
-    pub fn remove_gallery_if_empty( &mut self, gallery: &AttrValue )
+    /// Moves to the previous item of the open gallery, wrapping around to the last one.
+    pub fn prev( &mut self )
     {
-        let gallery = gallery.as_str();
+        let len = self.open_gallery_len();
+
+        if len > 0
+        {
+            self.open_index = ( self.open_index + len - 1 ) % len;
+            self.zoom = LightboxZoom::default();
+        }
+    }
+
+    /// Number of items of the open gallery.
+    pub fn open_gallery_len( &self ) -> usize
+    {
+        self.open_gallery
+            .as_ref()
+            .and_then( |gallery| self.gallery( gallery ) )
+            .map_or( 0, <[LightboxEntry]>::len )
+    }
+
+    /// Item shown by the open lightbox.
+    pub fn open_item( &self ) -> Option<LightboxEntry>
+    {
+        if !self.is_open
+        {
+            return None;
+        }
+
+        let gallery = self.open_gallery.as_ref()?;
+
+        self.gallery( gallery ).and_then( |items| items.get( self.open_index ) ).cloned()
+    }
+
+    fn index_of( &self, gallery: &str, data_src: &str ) -> Option<usize>
+    {
+        self.gallery( gallery )
+            .and_then( |items| items.iter().position( |item| item.data_src == data_src ) )
+    }
+
+    /// Updates the items of a gallery on new copies of the galleries, creating the gallery if needed
+    /// and removing it if it is left empty. The open item stays open when other items move around it.
+    fn update_gallery( &mut self, gallery: &str, update: impl FnOnce( &mut Vec<LightboxEntry> ) )
+    {
+        let open_data_src = self
+            .open_item()
+            .filter( |_| self.open_gallery.as_deref() == Some( gallery ) )
+            .map( |item| item.data_src );
+
+        let galleries = Rc::make_mut( &mut self.galleries );
+        let items = Rc::make_mut( galleries.entry( gallery.to_owned() ).or_default() );
+        update( items );
 
-        let mut data_sources_by_gallery = self.data_sources_by_gallery.borrow_mut();
+        if items.is_empty()
+        {
+            galleries.remove( gallery );
+        }
+
+        if let Some( index ) = open_data_src.and_then( |data_src| self.index_of( gallery, &data_src ) )
+        {
+            self.open_index = index;
+        }
+    }
+}
 
-        if let Some( data_sources ) = data_sources_by_gallery.get( gallery )
+/// Order of the elements of two items on the page, unmounted items last.
+fn page_order( a: &LightboxEntry, b: &LightboxEntry ) -> Ordering
+{
+    match ( a.node_ref.get(), b.node_ref.get() )
+    {
+        ( Some( a ), Some( b ) ) =>
         {
-            if data_sources.is_empty()
+            let position = a.compare_document_position( &b );
+
+            if position & Node::DOCUMENT_POSITION_FOLLOWING != 0
             {
-                data_sources_by_gallery.remove( gallery );
+                Ordering::Less
+            }
+            else if position & Node::DOCUMENT_POSITION_PRECEDING != 0
+            {
+                Ordering::Greater
+            }
+            else
+            {
+                Ordering::Equal
             }
         }
+        ( Some( _ ), None ) => Ordering::Less,
+        ( None, Some( _ ) ) => Ordering::Greater,
+        ( None, None ) => Ordering::Equal,
+    }
+}
+
+#[cfg( test )]
+#[allow( non_snake_case, clippy::float_cmp )]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn follow_link__item_added_later__opened_once_added()
+    {
+        let mut state = LightboxState::default();
+        let link = LightboxLink {
+            gallery:  "gallery".to_owned(),
+            data_src: "b.jpg".to_owned(),
+        };
+
+        state.follow_link( Some( link.clone() ) );
+        assert!( !state.is_open );
+        assert_eq!( state.link(), Some( link.clone() ) );
+
+        for data_src in ["a.jpg", "b.jpg"]
+        {
+            state.add_item(
+                &AttrValue::from( "gallery" ),
+                LightboxEntry {
+                    data_src: data_src.to_owned(),
+                    caption:  None,
+                    metadata: None,
+                    node_ref: NodeRef::default(),
+                    settings: LightboxItemSettings::default(),
+                },
+            );
+        }
+
+        assert!( state.is_open );
+        assert_eq!( state.open_index, 1 );
+        assert_eq!( state.link(), Some( link ) );
+
+        state.follow_link( None );
+        assert!( !state.is_open );
+        assert_eq!( state.link(), None );
+    }
+
+    #[test]
+    fn zoom_at__focus_point__stays_still()
+    {
+        let mut zoom = LightboxZoom::default();
+        zoom.zoom_at( 2.0, ( 100.0, 50.0 ), ( 800.0, 600.0 ), MAX_ZOOM_SCALE );
+
+        assert_eq!( zoom.scale, 2.0 );
+        assert_eq!( ( zoom.offset_x, zoom.offset_y ), ( -100.0, -50.0 ) );
+    }
+
+    #[test]
+    fn zoom_at__beyond_limits__clamped()
+    {
+        let mut zoom = LightboxZoom::default();
+
+        zoom.zoom_at( 0.5, ( 0.0, 0.0 ), ( 800.0, 600.0 ), MAX_ZOOM_SCALE );
+        assert_eq!( zoom, LightboxZoom::default() );
+
+        zoom.zoom_at( 10.0, ( 0.0, 0.0 ), ( 800.0, 600.0 ), MAX_ZOOM_SCALE );
+        assert_eq!( zoom.scale, MAX_ZOOM_SCALE );
+    }
+
+    #[test]
+    fn pan_by__past_the_edges__bounded_to_item()
+    {
+        let mut zoom = LightboxZoom::default();
+        zoom.zoom_at( 2.0, ( 0.0, 0.0 ), ( 800.0, 600.0 ), MAX_ZOOM_SCALE );
+        zoom.pan_by( ( 1000.0, -1000.0 ), ( 800.0, 600.0 ) );
+
+        assert_eq!( ( zoom.offset_x, zoom.offset_y ), ( 400.0, -300.0 ) );
     }
 }
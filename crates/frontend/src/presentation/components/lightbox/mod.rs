@@ -1,4 +1,72 @@
+pub mod events;
+pub mod info_panel_view;
 pub mod item_view;
 pub mod modal_view;
 
+mod deep_link;
+mod gesture;
 mod lightbox_state;
+
+/// Resets the lightbox state, global to the thread, so that a server render doesn't see the galleries and open
+/// item of the previous ones rendered on the same thread.
+#[cfg( feature = "ssr" )]
+pub fn reset_state()
+{
+    yewdux::prelude::Dispatch::<lightbox_state::LightboxState>::new().set( lightbox_state::LightboxState::default() );
+}
+
+#[cfg( all( test, feature = "ssr" ) )]
+#[allow( non_snake_case )]
+mod tests
+{
+    use super::{
+        item_view::LightboxItemSettings,
+        lightbox_state::{LightboxEntry, LightboxState},
+    };
+    use crate::{HeadHandle, RequestData, ResponseStatusHandle, ServerApp, ServerAppProps};
+    use futures::executor::block_on;
+    use yew::{AttrValue, LocalServerRenderer, NodeRef};
+    use yewdux::prelude::Dispatch;
+
+    /// Renders the app for a request with the link of the `a.jpg` item of `gallery`.
+    fn render_request_with_link() -> String
+    {
+        let props = ServerAppProps {
+            request_data:    RequestData {
+                url:     "/".to_owned(),
+                queries: vec![
+                    ( "gallery".to_owned(), "gallery".to_owned() ),
+                    ( "photo".to_owned(), "a.jpg".to_owned() ),
+                ],
+            },
+            response_status: ResponseStatusHandle::default(),
+            head:            HeadHandle::default(),
+        };
+
+        block_on( LocalServerRenderer::<ServerApp>::with_props( props ).render() )
+    }
+
+    #[test]
+    fn reset_state__second_request_after_the_lightbox_opened__lightbox_closed()
+    {
+        // The home page has no such item, the link of the first request is left pending.
+        let first = render_request_with_link();
+
+        // An item added by another render on the thread opens it.
+        Dispatch::<LightboxState>::new().reduce_mut( |state| {
+            state.add_item( &AttrValue::from( "gallery" ), LightboxEntry {
+                data_src: "a.jpg".to_owned(),
+                caption:  None,
+                metadata: None,
+                node_ref: NodeRef::default(),
+                settings: LightboxItemSettings::default(),
+            } );
+        } );
+        assert!( Dispatch::<LightboxState>::new().get().is_open );
+
+        let second = render_request_with_link();
+
+        assert!( !first.contains( "lightbox-modal" ) );
+        assert!( !second.contains( "lightbox-modal" ) );
+    }
+}
@@ -1,35 +1,101 @@
 use gloo::utils::document;
-use indexmap::IndexMap;
-use std::{ops::Deref, rc::Rc};
-use web_sys::*;
-use yew::{
-    html::IntoPropValue,
-    prelude::*,
-    virtual_dom::{ApplyAttributeAs, Attributes, VNode},
-};
+use std::rc::Rc;
+use web_sys::{Element, HtmlElement, HtmlImageElement, KeyboardEvent, MouseEvent, Node, PointerEvent, WheelEvent};
+use yew::prelude::*;
 
-use super::lightbox_state::LightboxState;
+use super::{
+    deep_link,
+    events::{LightboxEvent, LightboxPlugin},
+    gesture::{Gesture, GestureTracker},
+    info_panel_view::LightboxInfoPanel,
+    lightbox_state::{LightboxLink, LightboxState, LightboxZoom},
+};
 use crate::{
-    features::base_component::{BasePluginComponent, SettingValue, Settings},
-    presentation::components::custom_children_container::CustomChildrenContainer,
+    features::head::Head,
     utils::{unwrap_abort, unwrap_r_abort},
 };
-use gloo::events::EventListener;
-use yew_router::prelude::RouterScopeExt;
+use gloo::events::{EventListener, EventListenerOptions};
+use wasm_bindgen::JsCast;
+use yew_router::{
+    history::{BrowserHistory, History, Location},
+    prelude::RouterScopeExt,
+    scope_ext::LocationHandle,
+};
 use yewdux::prelude::Dispatch;
 
+/// Id of the caption labelling the dialog.
+const CAPTION_ID: &str = "lightbox-modal-caption";
+
+/// Elements of the dialog that can be focused with the keyboard.
+const FOCUSABLE_SELECTOR: &str =
+    "a[href], button:not([disabled]), input, select, textarea, [tabindex]:not([tabindex=\"-1\"])";
+
+/// Zoom scale of a double tap on the open item.
+const DOUBLE_TAP_ZOOM_SCALE: f64 = 2.5;
+
+/// Zoom speed of the mouse wheel, per pixel scrolled.
+const WHEEL_ZOOM_SPEED: f64 = 0.002;
+
+/// Pixels scrolled per line, for wheels that scroll by lines.
+const WHEEL_LINE_HEIGHT: f64 = 16.0;
+
 #[derive(Properties, PartialEq)]
-pub struct LightboxModalProps {}
+pub struct LightboxModalProps
+{
+    #[prop_or( Some( AttrValue::from( "i" ) ) )]
+    pub info_panel_key:        Option<AttrValue>, // key toggling the metadata panel, `None` disables the panel.
+    #[prop_or( Some( AttrValue::from( "s" ) ) )]
+    pub slideshow_key:         Option<AttrValue>, // key toggling the slideshow, `None` disables the slideshow.
+    #[prop_or( 5000 )]
+    pub slideshow_interval_ms: u32, // time each item is shown by the slideshow.
+    #[prop_or_default]
+    pub plugin:                Option<LightboxPlugin>, // settings and event listeners of the lightbox.
+}
 
 pub enum LightboxModalMsg
 {
     State( Rc<LightboxState> ),
+    Location( Location ),
+    KeyDown( String ),
+    Close,
+    Next,
+    Prev,
+    PointerDown( PointerEvent ),
+    PointerMove( PointerEvent ),
+    PointerUp( PointerEvent ),
+    PointerCancel( PointerEvent ),
+    Wheel( WheelEvent ),
+    ToggleSlideshow,
+    SlideshowNext,
+    Hover( bool ),
 }
 
 pub struct LightboxModal
 {
-    state:    Rc<LightboxState>,
-    dispatch: Dispatch<LightboxState>,
+    state:            Rc<LightboxState>,
+    dispatch:         Dispatch<LightboxState>,
+    is_info_panel_on: bool,
+    keydown_listener: Option<EventListener>,
+    wheel_listener:   Option<EventListener>,
+    _location_handle: Option<LocationHandle>,
+    has_pushed_link:  bool,
+    container_ref:    NodeRef,
+    image_ref:        NodeRef,
+    gestures:         GestureTracker,
+    slideshow:        Slideshow,
+    preloaded_images: Vec<HtmlImageElement>,
+
+    // Dialog, see `LightboxModal::on_open` and `LightboxModal::on_close`.
+    was_open:         bool,
+    close_button_ref: NodeRef,
+    return_focus_ref: Option<NodeRef>,
+    focusin_listener: Option<EventListener>,
+    body_overflow:    Option<String>,
+
+    // Events, see `LightboxModal::emit_events`.
+    plugin:        LightboxPlugin,
+    emitted_link:  Option<LightboxLink>,
+    emitted_scale: f64,
 }
 
 impl Component for LightboxModal
@@ -42,36 +108,766 @@ impl Component for LightboxModal
         let link = ctx.link();
         let dispatch = Dispatch::<LightboxState>::subscribe( link.callback( LightboxModalMsg::State ) );
 
+        // Follow the link of the page, on the server too so that the open item is rendered.
+        if let Some( location ) = link.location()
+        {
+            let page_link = deep_link::from_query( &Self::queries( &location ) );
+            dispatch.reduce_mut( |state| state.follow_link( page_link ) );
+        }
+
         Self {
             state: dispatch.get(),
             dispatch,
+            is_info_panel_on: false,
+            keydown_listener: None,
+            wheel_listener: None,
+            _location_handle: link.add_location_listener( link.callback( LightboxModalMsg::Location ) ),
+            has_pushed_link: false,
+            container_ref: NodeRef::default(),
+            image_ref: NodeRef::default(),
+            gestures: GestureTracker::default(),
+            slideshow: Slideshow::default(),
+            preloaded_images: Vec::new(),
+            was_open: false,
+            close_button_ref: NodeRef::default(),
+            return_focus_ref: None,
+            focusin_listener: None,
+            body_overflow: None,
+            plugin: ctx.props().plugin.clone().unwrap_or_default(),
+            emitted_link: None,
+            emitted_scale: 1.0,
         }
     }
 
-    fn update( &mut self, _ctx: &Context<Self>, msg: Self::Message ) -> bool
+    fn update( &mut self, ctx: &Context<Self>, msg: Self::Message ) -> bool
     {
         match msg
         {
             LightboxModalMsg::State( state ) =>
             {
+                if !state.is_open
+                {
+                    self.gestures = GestureTracker::default();
+                    self.slideshow.is_on = false;
+                }
+
                 self.state = state;
                 true
             }
+            LightboxModalMsg::Location( location ) =>
+            {
+                // Follow the history, e.g. when going back to the page with the lightbox closed.
+                let page_link = deep_link::from_query( &Self::queries( &location ) );
+                self.has_pushed_link &= page_link.is_some();
+                self.dispatch.reduce_mut( |state| state.follow_link( page_link ) );
+
+                false
+            }
+            LightboxModalMsg::KeyDown( key ) => self.handle_key( ctx, &key ),
+            LightboxModalMsg::Close =>
+            {
+                self.dispatch.reduce_mut( LightboxState::close );
+                false
+            }
+            LightboxModalMsg::Next =>
+            {
+                self.dispatch.reduce_mut( LightboxState::next );
+                false
+            }
+            LightboxModalMsg::Prev =>
+            {
+                self.dispatch.reduce_mut( LightboxState::prev );
+                false
+            }
+            LightboxModalMsg::PointerDown( event ) => self.handle_pointer_down( &event ),
+            LightboxModalMsg::PointerMove( event ) => self.handle_pointer_move( &event ),
+            LightboxModalMsg::PointerUp( event ) => self.handle_pointer_up( ctx, &event ),
+            LightboxModalMsg::PointerCancel( event ) =>
+            {
+                self.gestures.cancel( event.pointer_id() );
+                !self.gestures.is_active()
+            }
+            LightboxModalMsg::Wheel( event ) => self.handle_wheel( &event ),
+            LightboxModalMsg::ToggleSlideshow =>
+            {
+                self.slideshow.is_on = !self.slideshow.is_on && self.state.open_gallery_len() > 1;
+                self.slideshow.restarts = 0;
+                true
+            }
+            LightboxModalMsg::SlideshowNext =>
+            {
+                if self.slideshow.is_on
+                {
+                    self.dispatch.reduce_mut( LightboxState::next );
+                }
+
+                false
+            }
+            LightboxModalMsg::Hover( is_hovered ) =>
+            {
+                self.slideshow.is_hovered = is_hovered;
+                self.slideshow.is_on
+            }
         }
     }
 
+    fn changed( &mut self, ctx: &Context<Self>, _old_props: &Self::Properties ) -> bool
+    {
+        if let Some( plugin ) = ctx.props().plugin.clone()
+        {
+            self.plugin = plugin;
+        }
+
+        true
+    }
+
     fn view( &self, ctx: &Context<Self> ) -> Html
     {
+        let Some( item ) = self.state.open_item() else { return html! {} };
+
+        let link = ctx.link();
+
+        let info_panel = if self.is_info_panel_on && ctx.props().info_panel_key.is_some()
+        {
+            item.metadata.clone()
+        }
+        else
+        {
+            None
+        };
+
+        let zoom = self.state.zoom;
+        let image_class = classes!(
+            "lightbox-modal__image",
+            zoom.is_zoomed().then_some( "lightbox-modal__image--zoomed" ),
+            self.gestures.is_active().then_some( "lightbox-modal__image--gesturing" )
+        );
+        let image_style = format!(
+            "transform: translate({}px, {}px) scale({});",
+            zoom.offset_x, zoom.offset_y, zoom.scale
+        );
+
+        // Clicks on the backdrop close the lightbox, clicks on its content don't.
+        let onclick_backdrop = link.batch_callback( |event: MouseEvent| {
+            ( event.target() == event.current_target() ).then_some( LightboxModalMsg::Close )
+        } );
+
+        // Keep the focus on the dialog when tabbing past its first or last element.
+        let onkeydown = {
+            let container_ref = self.container_ref.clone();
+            Callback::from( move |event: KeyboardEvent| Self::trap_focus( &container_ref, &event ) )
+        };
+
         html! {
+            <div
+                class="lightbox-modal__container"
+                ref={self.container_ref.clone()}
+                role="dialog"
+                aria-modal="true"
+                aria-labelledby={item.caption.is_some().then_some( CAPTION_ID )}
+                aria-label={item.caption.is_none().then_some( "Photo" )}
+                tabindex="-1"
+                onclick={onclick_backdrop}
+                {onkeydown}
+            >
+                <figure
+                    class="lightbox-modal__figure"
+                    onmouseenter={link.callback( |_| LightboxModalMsg::Hover( true ) )}
+                    onmouseleave={link.callback( |_| LightboxModalMsg::Hover( false ) )}
+                >
+                    <img
+                        class={image_class}
+                        style={image_style}
+                        ref={self.image_ref.clone()}
+                        src={item.data_src.clone()}
+                        alt={item.caption.clone().unwrap_or_default()}
+                        draggable="false"
+                        onpointerdown={link.callback( LightboxModalMsg::PointerDown )}
+                        onpointermove={link.callback( LightboxModalMsg::PointerMove )}
+                        onpointerup={link.callback( LightboxModalMsg::PointerUp )}
+                        onpointercancel={link.callback( LightboxModalMsg::PointerCancel )}
+                    />
+                    if let Some( caption ) = item.caption.clone()
+                    {
+                        <figcaption id={CAPTION_ID} class="lightbox-modal__caption">{caption}</figcaption>
+                    }
+                </figure>
+
+                {self.view_gallery_controls( ctx )}
+
+                <button
+                    type="button"
+                    ref={self.close_button_ref.clone()}
+                    class="lightbox-modal__button lightbox-modal__button--close"
+                    aria-label="Close"
+                    onclick={link.callback( |_| LightboxModalMsg::Close )}
+                >
+                    {"×"}
+                </button>
+
+                if let Some( metadata ) = info_panel
+                {
+                    <LightboxInfoPanel {metadata} />
+                }
+
+                // Previews of a link to the open item show it.
+                <Head
+                    description={item.caption.clone().map( AttrValue::from )}
+                    og_image={AttrValue::from( item.data_src.clone() )}
+                />
+            </div>
+        }
+    }
+
+    fn rendered( &mut self, ctx: &Context<Self>, first_render: bool )
+    {
+        // Listen to the keyboard on the client only, once mounted.
+        if first_render
+        {
+            self.keydown_listener = Some( Self::on_keydown( ctx ) );
+        }
+
+        self.update_page_link( ctx );
+        self.preload_adjacent_images();
+
+        if let Some( item ) = self.state.open_item()
+        {
+            self.return_focus_ref = Some( item.node_ref );
+        }
+
+        if self.state.is_open != self.was_open
+        {
+            self.was_open = self.state.is_open;
+
             if self.state.is_open
             {
-                <div class={"lightbox-modal__container"}>
-                </div>
+                self.on_open();
             }
             else
             {
-                <></>
+                self.on_close();
+            }
+        }
+
+        self.emit_events();
+
+        // The wheel listener lives on the container, which only exists while the lightbox is open.
+        if !self.state.is_open
+        {
+            self.wheel_listener = None;
+        }
+        else if self.wheel_listener.is_none()
+        {
+            self.wheel_listener = self
+                .container_ref
+                .get()
+                .map( |container| Self::on_wheel( ctx, &container ) );
+        }
+    }
+
+    fn destroy( &mut self, _ctx: &Context<Self> ) { self.plugin.off_all(); }
+}
+
+impl LightboxModal
+{
+    /// Controls the open lightbox with the keyboard, returns whether to render.
+    fn handle_key( &mut self, ctx: &Context<Self>, key: &str ) -> bool
+    {
+        // Keys only control the lightbox while it is open.
+        if !self.state.is_open
+        {
+            return false;
+        }
+
+        let is_restarted = self.slideshow.restart();
+        let props = ctx.props();
+
+        match key
+        {
+            "Escape" => ctx.link().send_message( LightboxModalMsg::Close ),
+            "ArrowRight" => ctx.link().send_message( LightboxModalMsg::Next ),
+            "ArrowLeft" => ctx.link().send_message( LightboxModalMsg::Prev ),
+            key if props.info_panel_key.as_ref().is_some_and( |panel_key| key.eq_ignore_ascii_case( panel_key ) ) =>
+            {
+                self.is_info_panel_on = !self.is_info_panel_on;
+                return true;
+            }
+            key if props.slideshow_key.as_ref().is_some_and( |slide_key| key.eq_ignore_ascii_case( slide_key ) ) =>
+            {
+                ctx.link().send_message( LightboxModalMsg::ToggleSlideshow );
+            }
+            _ => (),
+        }
+
+        is_restarted
+    }
+
+    /// Starts a gesture, returns whether to render.
+    fn handle_pointer_down( &mut self, event: &PointerEvent ) -> bool
+    {
+        // Only the main mouse button starts a gesture.
+        if event.pointer_type() == "mouse" && event.button() != 0
+        {
+            return false;
+        }
+
+        // Keep receiving the pointer when it leaves the image.
+        if let Some( target ) = event.target_dyn_into::<Element>()
+        {
+            let _ = target.set_pointer_capture( event.pointer_id() );
+        }
+
+        self.slideshow.restart();
+
+        let was_active = self.gestures.is_active();
+        self.gestures.down(
+            event.pointer_id(),
+            f64::from( event.client_x() ),
+            f64::from( event.client_y() ),
+            event.time_stamp(),
+        );
+
+        !was_active
+    }
+
+    /// Pans or pinches the item while a gesture moves, the state change renders it.
+    fn handle_pointer_move( &mut self, event: &PointerEvent ) -> bool
+    {
+        let gesture = self.gestures.move_to(
+            event.pointer_id(),
+            f64::from( event.client_x() ),
+            f64::from( event.client_y() ),
+            event.time_stamp(),
+        );
+
+        match gesture
+        {
+            // Moving one pointer pans the zoomed item, swipes are recognized once released.
+            Gesture::Pan( dx, dy ) if self.state.zoom.is_zoomed() => self.pan_by( dx, dy ),
+            Gesture::Pinch { factor, focus } => self.zoom_at( factor, focus ),
+            _ => (),
+        }
+
+        false
+    }
+
+    /// Ends a gesture, acting on the recognized swipe or double tap, returns whether to render.
+    fn handle_pointer_up( &mut self, ctx: &Context<Self>, event: &PointerEvent ) -> bool
+    {
+        let gesture = self.gestures.up(
+            event.pointer_id(),
+            f64::from( event.client_x() ),
+            f64::from( event.client_y() ),
+            event.time_stamp(),
+            self.state.zoom.is_zoomed(),
+        );
+
+        match gesture
+        {
+            Gesture::SwipeLeft => ctx.link().send_message( LightboxModalMsg::Next ),
+            Gesture::SwipeRight => ctx.link().send_message( LightboxModalMsg::Prev ),
+            Gesture::SwipeDown => ctx.link().send_message( LightboxModalMsg::Close ),
+            Gesture::DoubleTap( .. ) if self.state.zoom.is_zoomed() =>
+            {
+                self.dispatch.reduce_mut( |state| state.zoom = LightboxZoom::default() );
+            }
+            Gesture::DoubleTap( x, y ) => self.zoom_at( DOUBLE_TAP_ZOOM_SCALE, ( x, y ) ),
+            _ => (),
+        }
+
+        !self.gestures.is_active()
+    }
+
+    /// Zooms with the wheel around the pointer, returns whether to render.
+    fn handle_wheel( &mut self, event: &WheelEvent ) -> bool
+    {
+        let delta = match event.delta_mode()
+        {
+            WheelEvent::DOM_DELTA_LINE => event.delta_y() * WHEEL_LINE_HEIGHT,
+            _ => event.delta_y(),
+        };
+
+        self.zoom_at(
+            ( -delta * WHEEL_ZOOM_SPEED ).exp(),
+            ( f64::from( event.client_x() ), f64::from( event.client_y() ) ),
+        );
+
+        self.slideshow.restart()
+    }
+
+    fn on_keydown( ctx: &Context<Self> ) -> EventListener
+    {
+        let link = ctx.link().clone();
+
+        EventListener::new( &document(), "keydown", move |event| {
+            let event = unwrap_abort( event.dyn_ref::<KeyboardEvent>() );
+
+            // Ignore keys with modifiers, they belong to the browser.
+            if event.ctrl_key() || event.meta_key() || event.alt_key()
+            {
+                return;
+            }
+
+            link.send_message( LightboxModalMsg::KeyDown( event.key() ) );
+        } )
+    }
+
+    /// Emits the events of the changes of the state since the last render.
+    fn emit_events( &mut self )
+    {
+        let link = if self.state.is_open { self.state.link() } else { None };
+        let index = self.state.open_index;
+
+        let event = match ( self.emitted_link.as_ref(), link.clone() )
+        {
+            ( None, Some( LightboxLink { gallery, data_src } ) ) => Some( LightboxEvent::Open {
+                gallery,
+                data_src,
+                index,
+            } ),
+            ( Some( _ ), None ) => Some( LightboxEvent::Close ),
+            ( Some( emitted_link ), Some( LightboxLink { gallery, data_src } ) )
+                if emitted_link.gallery != gallery || emitted_link.data_src != data_src =>
+            {
+                Some( LightboxEvent::Change {
+                    gallery,
+                    data_src,
+                    index,
+                } )
             }
+            _ => None,
+        };
+
+        if let Some( event ) = event
+        {
+            self.plugin.emit( &event );
+        }
+
+        let scale = self.state.zoom.scale;
+
+        if link.is_some() && ( scale - self.emitted_scale ).abs() > f64::EPSILON
+        {
+            self.plugin.emit( &LightboxEvent::Zoom { scale } );
+        }
+
+        self.emitted_link = link;
+        self.emitted_scale = scale;
+    }
+
+    /// Moves the focus into the dialog, keeps it there and locks the scroll of the page behind.
+    fn on_open( &mut self )
+    {
+        if let Some( body ) = document().body()
+        {
+            let style = body.style();
+            self.body_overflow = style.get_property_value( "overflow" ).ok();
+            let _ = style.set_property( "overflow", "hidden" );
+        }
+
+        Self::focus( &self.close_button_ref );
+
+        let container_ref = self.container_ref.clone();
+        let close_button_ref = self.close_button_ref.clone();
+
+        self.focusin_listener = Some( EventListener::new( &document(), "focusin", move |event| {
+            let target = event.target().and_then( |target| target.dyn_into::<Node>().ok() );
+
+            if container_ref.get().is_some_and( |container| !container.contains( target.as_ref() ) )
+            {
+                Self::focus( &close_button_ref );
+            }
+        } ) );
+    }
+
+    /// Unlocks the scroll of the page and returns the focus to the element of the last item shown.
+    fn on_close( &mut self )
+    {
+        self.focusin_listener = None;
+
+        if let ( Some( body ), Some( overflow ) ) = ( document().body(), self.body_overflow.take() )
+        {
+            let _ = body.style().set_property( "overflow", &overflow );
         }
+
+        if let Some( return_focus_ref ) = self.return_focus_ref.take()
+        {
+            Self::focus( &return_focus_ref );
+        }
+    }
+
+    fn focus( node_ref: &NodeRef )
+    {
+        if let Some( element ) = node_ref.cast::<HtmlElement>()
+        {
+            let _ = element.focus();
+        }
+    }
+
+    /// Wraps the focus around the focusable elements of the dialog on `Tab` and `Shift+Tab`.
+    fn trap_focus( container_ref: &NodeRef, event: &KeyboardEvent )
+    {
+        if event.key() != "Tab"
+        {
+            return;
+        }
+
+        let focusables = match container_ref
+            .cast::<Element>()
+            .and_then( |container| container.query_selector_all( FOCUSABLE_SELECTOR ).ok() )
+        {
+            Some( focusables ) if focusables.length() > 0 => focusables,
+            _ => return,
+        };
+
+        let first = focusables.get( 0 );
+        let last = focusables.get( focusables.length() - 1 );
+        let active = document().active_element().map( Node::from );
+
+        // The dialog itself counts as before its first element.
+        let is_on_first = active == first || active == container_ref.get();
+        let next = match ( event.shift_key(), is_on_first, active == last )
+        {
+            ( true, true, _ ) => last,
+            ( false, _, true ) => first,
+            _ => return,
+        };
+
+        if let Some( next ) = next.and_then( |next| next.dyn_into::<HtmlElement>().ok() )
+        {
+            event.prevent_default();
+            let _ = next.focus();
+        }
+    }
+
+    /// Counter, previous and next buttons, and slideshow controls of a gallery of many items.
+    fn view_gallery_controls( &self, ctx: &Context<Self> ) -> Html
+    {
+        let link = ctx.link();
+        let len = self.state.open_gallery_len();
+        let has_many_items = len > 1;
+
+        html! {
+            <>
+                if has_many_items
+                {
+                    <span class="lightbox-modal__counter" aria-live="polite">
+                        {format!( "{} / {}", self.state.open_index + 1, len )}
+                    </span>
+
+                    <button
+                        type="button"
+                        class="lightbox-modal__button lightbox-modal__button--prev"
+                        aria-label="Previous"
+                        onclick={link.callback( |_| LightboxModalMsg::Prev )}
+                    >
+                        {"‹"}
+                    </button>
+                    <button
+                        type="button"
+                        class="lightbox-modal__button lightbox-modal__button--next"
+                        aria-label="Next"
+                        onclick={link.callback( |_| LightboxModalMsg::Next )}
+                    >
+                        {"›"}
+                    </button>
+                }
+
+                if has_many_items && ctx.props().slideshow_key.is_some()
+                {
+                    <button
+                        type="button"
+                        class="lightbox-modal__button lightbox-modal__button--slideshow"
+                        aria-label={if self.slideshow.is_on { "Pause slideshow" } else { "Play slideshow" }}
+                        aria-pressed={self.slideshow.is_on.to_string()}
+                        onclick={link.callback( |_| LightboxModalMsg::ToggleSlideshow )}
+                    >
+                        {if self.slideshow.is_on { "❚❚" } else { "▶" }}
+                    </button>
+                }
+
+                if self.slideshow.is_on
+                {
+                    {self.view_slideshow_progress( ctx )}
+                }
+            </>
+        }
+    }
+
+    /// Progress bar of the slideshow, moving to the next item at the end of its animation. The bar is
+    /// recreated to restart it on each item and interaction, and its animation is paused on hover and
+    /// while the item is zoomed.
+    fn view_slideshow_progress( &self, ctx: &Context<Self> ) -> Html
+    {
+        let is_paused = self.slideshow.is_hovered || self.gestures.is_active() || self.state.zoom.is_zoomed();
+
+        html! {
+            <div class="lightbox-modal__progress">
+                <div
+                    key={format!( "{}-{}", self.state.open_index, self.slideshow.restarts )}
+                    class={classes!(
+                        "lightbox-modal__progress-bar",
+                        is_paused.then_some( "lightbox-modal__progress-bar--paused" )
+                    )}
+                    style={format!( "animation-duration: {}ms;", ctx.props().slideshow_interval_ms )}
+                    onanimationend={ctx.link().callback( |_| LightboxModalMsg::SlideshowNext )}
+                />
+            </div>
+        }
+    }
+
+    /// Loads and decodes the items next to the open one, so that moving to them shows them at once.
+    fn preload_adjacent_images( &mut self )
+    {
+        let data_srcs = self
+            .state
+            .adjacent_items()
+            .into_iter()
+            .map( |item| item.data_src )
+            .collect::<Vec<_>>();
+
+        // Keep the images already preloading.
+        self.preloaded_images.retain( |image| data_srcs.contains( &image.get_attribute( "src" ).unwrap_or_default() ) );
+
+        for data_src in data_srcs
+        {
+            if self
+                .preloaded_images
+                .iter()
+                .any( |image| image.get_attribute( "src" ).as_deref() == Some( &data_src ) )
+            {
+                continue;
+            }
+
+            if let Ok( image ) = HtmlImageElement::new()
+            {
+                image.set_src( &data_src );
+                let _ = image.decode();
+                self.preloaded_images.push( image );
+            }
+        }
+    }
+
+    fn queries( location: &Location ) -> Vec<( String, String )> { location.query().unwrap_or_default() }
+
+    /// Keeps the link of the open item on the page url. Opening the lightbox adds a history entry, so
+    /// that going back closes it, and moving between items replaces it.
+    fn update_page_link( &mut self, ctx: &Context<Self> )
+    {
+        let Some( location ) = ctx.link().location() else { return };
+
+        let queries = Self::queries( &location );
+        let page_link = deep_link::from_query( &queries );
+        let link = self.state.link();
+
+        if page_link == link
+        {
+            return;
+        }
+
+        let history = BrowserHistory::new();
+        let queries = deep_link::with_link( queries, link.as_ref() );
+
+        let result = match ( page_link.is_some(), link.is_some() )
+        {
+            ( false, true ) =>
+            {
+                self.has_pushed_link = true;
+                history.push_with_query( location.path(), queries )
+            }
+            ( true, false ) if self.has_pushed_link =>
+            {
+                self.has_pushed_link = false;
+                history.back();
+                Ok( () )
+            }
+            _ => history.replace_with_query( location.path(), queries ),
+        };
+
+        unwrap_r_abort( result );
+    }
+
+    /// Listens to the wheel with a non passive listener, so that zooming doesn't scroll the page. Yew
+    /// registers its `onwheel` listeners as passive.
+    fn on_wheel( ctx: &Context<Self>, container: &Node ) -> EventListener
+    {
+        let link = ctx.link().clone();
+
+        EventListener::new_with_options(
+            container,
+            "wheel",
+            EventListenerOptions::enable_prevent_default(),
+            move |event| {
+                let event = unwrap_abort( event.dyn_ref::<WheelEvent>() );
+                event.prevent_default();
+
+                link.send_message( LightboxModalMsg::Wheel( event.clone() ) );
+            },
+        )
+    }
+
+    /// Zooms the open item by a factor around a point of the viewport.
+    fn zoom_at( &self, factor: f64, point: ( f64, f64 ) )
+    {
+        let settings = match self.state.open_item()
+        {
+            Some( item ) if item.settings.is_zoomable => item.settings,
+            _ => return,
+        };
+
+        if let Some( ( center, bounds ) ) = self.image_geometry()
+        {
+            let focus = ( point.0 - center.0, point.1 - center.1 );
+            self.dispatch
+                .reduce_mut( move |state| state.zoom.zoom_at( factor, focus, bounds, settings.max_zoom_scale ) );
+        }
+    }
+
+    fn pan_by( &self, dx: f64, dy: f64 )
+    {
+        if let Some( ( _, bounds ) ) = self.image_geometry()
+        {
+            self.dispatch.reduce_mut( move |state| state.zoom.pan_by( ( dx, dy ), bounds ) );
+        }
+    }
+
+    /// Center in the viewport and size of the open item as fitted, without its zoom.
+    fn image_geometry( &self ) -> Option<( ( f64, f64 ), ( f64, f64 ) )>
+    {
+        let image = self.image_ref.cast::<HtmlElement>()?;
+        let rect = image.get_bounding_client_rect();
+        let zoom = self.state.zoom;
+
+        // The item is scaled around its center, so only the offset moves the center.
+        let center = (
+            rect.left() + rect.width() / 2.0 - zoom.offset_x,
+            rect.top() + rect.height() / 2.0 - zoom.offset_y,
+        );
+        let bounds = ( f64::from( image.offset_width() ), f64::from( image.offset_height() ) );
+
+        Some( ( center, bounds ) )
+    }
+}
+
+/// Slideshow of the open gallery, see `LightboxModal::view_slideshow_progress`.
+#[derive(Default)]
+struct Slideshow
+{
+    is_on:      bool,
+    /// Keys the progress bar, so that it restarts on each interaction.
+    restarts:   u32,
+    is_hovered: bool,
+}
+
+impl Slideshow
+{
+    /// Restarts the progress after an interaction, returns whether the slideshow is on.
+    const fn restart( &mut self ) -> bool
+    {
+        if self.is_on
+        {
+            self.restarts = self.restarts.wrapping_add( 1 );
+        }
+
+        self.is_on
     }
 }
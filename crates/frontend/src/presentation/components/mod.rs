@@ -1,2 +1,3 @@
 pub mod custom_children_container;
 pub mod lightbox;
+pub mod picture;
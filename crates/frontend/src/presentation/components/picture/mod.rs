@@ -0,0 +1,77 @@
+use common::dtos::photos::{PhotoDerivativeResponse, PhotoFileResponse};
+use yew::prelude::*;
+
+/// Formats offered as `<source>` elements, most preferred first. Browsers that support none of them
+/// fall back to the `<img>` element.
+const SOURCE_MIME_TYPES: [&str; 2] = ["image/avif", "image/webp"];
+
+/// Format of the fallback `<img>` element, supported by every browser.
+const FALLBACK_MIME_TYPE: &str = "image/jpeg";
+
+#[derive(Properties, PartialEq)]
+pub struct PictureProps
+{
+    // General props.
+    #[prop_or_default]
+    pub class: Option<Classes>,
+
+    // Specific props.
+    pub file:    PhotoFileResponse, // photo file with its derivatives.
+    pub alt:     AttrValue, // alternative text of the image.
+    #[prop_or( AttrValue::from( "100vw" ) )]
+    pub sizes:   AttrValue, // rendered width of the image, to pick the derivative size.
+    #[prop_or( AttrValue::from( "lazy" ) )]
+    pub loading: AttrValue, // `lazy` or `eager` loading of the image.
+}
+
+/// Responsive picture of a photo file.
+///
+/// Lets the browser pick the best format and size among the derivatives generated by the backend,
+/// falling back to the original file if there are none.
+#[function_component( Picture )]
+pub fn picture( props: &PictureProps ) -> Html
+{
+    let derivatives = &props.file.derivatives;
+
+    let sources = SOURCE_MIME_TYPES
+        .iter()
+        .filter_map( |mime_type| srcset( derivatives, mime_type ).map( |srcset| ( mime_type, srcset ) ) )
+        .map( |( mime_type, srcset )| {
+            html! { <source type={*mime_type} {srcset} sizes={props.sizes.clone()} /> }
+        } )
+        .collect::<Html>();
+
+    // The largest fallback derivative sets the intrinsic size, to avoid layout shifts.
+    let largest = derivatives
+        .iter()
+        .filter( |derivative| derivative.mime_type == FALLBACK_MIME_TYPE )
+        .max_by_key( |derivative| derivative.width );
+
+    html! {
+        <picture class={props.class.clone()}>
+            {sources}
+            <img
+                src={largest.map_or_else( || props.file.url.clone(), |derivative| derivative.url.clone() )}
+                srcset={srcset( derivatives, FALLBACK_MIME_TYPE )}
+                sizes={props.sizes.clone()}
+                width={largest.map( |derivative| derivative.width.to_string() )}
+                height={largest.map( |derivative| derivative.height.to_string() )}
+                alt={props.alt.clone()}
+                loading={props.loading.clone()}
+                decoding="async"
+            />
+        </picture>
+    }
+}
+
+/// Source set of the derivatives of a format, `None` if there are none.
+fn srcset( derivatives: &[PhotoDerivativeResponse], mime_type: &str ) -> Option<String>
+{
+    let candidates = derivatives
+        .iter()
+        .filter( |derivative| derivative.mime_type == mime_type )
+        .map( |derivative| format!( "{} {}w", derivative.url, derivative.width ) )
+        .collect::<Vec<_>>();
+
+    ( !candidates.is_empty() ).then( || candidates.join( ", " ) )
+}
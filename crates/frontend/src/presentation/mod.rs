@@ -2,4 +2,3 @@ pub mod by_features;
 pub mod components;
 pub mod layout;
 pub mod routes;
-pub mod utils;
@@ -1,4 +1,4 @@
-use crate::presentation::by_features;
+use crate::{features::head::Head, presentation::by_features};
 use yew::{html, Html};
 use yew_router::prelude::*;
 
@@ -21,11 +21,12 @@ pub fn switch( routes: Route ) -> Html
     {
         Route::Home => html! {
             <>
+            <Head title="Photo Story" description="Stories told through photo albums." />
             <h1 class="text-9xl font-bold underline">{ "Home" }</h1>
             <Link<Route> to={Route::HelloServer}>{ "click here to go to hello-server" }</Link<Route>>
             </>
         },
         Route::HelloServer => by_features::hello_server::component(),
-        Route::NotFound => html! { <h1>{ "404" }</h1> },
+        Route::NotFound => by_features::not_found::component(),
     }
 }
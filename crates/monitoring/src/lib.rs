@@ -7,7 +7,6 @@
 #![warn( clippy::nursery )]
 #![warn( clippy::complexity )]
 #![warn( clippy::perf )]
-#![feature( once_cell )]
 
 pub mod logger;
 pub mod prometheus;
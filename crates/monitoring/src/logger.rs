@@ -21,7 +21,8 @@
 
 use axum::{
     body::{Body, BoxBody, Bytes},
-    http::{HeaderMap, Request, Response},
+    http::{HeaderMap, HeaderValue, Request, Response},
+    middleware::{self, Next},
     Router,
 };
 use std::time::Duration;
@@ -131,12 +132,31 @@ pub fn init(
     ( guard_io_writer, guard_file_writer )
 }
 
+/// Response header of the id of the request, the one of its logs.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Id of the request being handled, set by [`middleware_http_tracing`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId( pub String );
+
+/// Returns the id of the request being handled, if called while handling one behind [`middleware_http_tracing`],
+/// e.g. to report it in the error responses.
+#[must_use]
+pub fn current_request_id() -> Option<String> { REQUEST_ID.try_with( Clone::clone ).ok() }
+
 #[must_use]
 pub fn middleware_http_tracing( router: Router ) -> Router
 {
     let trace_layer = http_trace::TraceLayer::new_for_http()
-        .make_span_with( |_request: &Request<Body>| {
-            let request_id = Uuid::new_v4().to_string();
+        .make_span_with( |request: &Request<Body>| {
+            let request_id = request
+                .extensions()
+                .get::<RequestId>()
+                .map_or_else( || Uuid::new_v4().to_string(), |request_id| request_id.0.clone() );
             tracing::info_span!("HTTP", %request_id)
         } )
         .on_request( |request: &Request<Body>, _span: &Span| {
@@ -157,5 +177,23 @@ pub fn middleware_http_tracing( router: Router ) -> Router
             tracing::error!( "ERROR{{{}}}", error );
         } );
 
-    router.layer( trace_layer )
+    // The id is set before the trace layer so its span and the handlers share it.
+    router.layer( trace_layer ).layer( middleware::from_fn( scope_request_id ) )
+}
+
+/// Generates the id of the request, available to the handlers through [`current_request_id`] and returned in the
+/// [`REQUEST_ID_HEADER`] header.
+async fn scope_request_id( mut request: Request<Body>, next: Next<Body> ) -> Response<BoxBody>
+{
+    let request_id = Uuid::new_v4().to_string();
+    request.extensions_mut().insert( RequestId( request_id.clone() ) );
+
+    let mut response = REQUEST_ID.scope( request_id.clone(), next.run( request ) ).await;
+
+    if let Ok( header_value ) = HeaderValue::from_str( &request_id )
+    {
+        response.headers_mut().insert( REQUEST_ID_HEADER, header_value );
+    }
+
+    response
 }
@@ -1,18 +1,17 @@
 //! Provides metrics collection for prometheus.
 //!
-//! Provides a set of metrics that can be used to collect data for prometheus, as well as a function
-//! to add them to a registry.
+//! Provides a set of metrics that can be used to collect data for prometheus, a function to add them
+//! to a registry, a function to encode a registry in the text exposition format and an http
+//! middleware that records the metrics for each request.
 //!
 //! # Examples
 //! ```
-//! use lazy_static::lazy_static;
 //! use monitoring::prometheus;
 //! use monitoring::prometheus::metrics;
 //! use ::prometheus::Registry;
+//! use std::sync::LazyLock;
 //!
-//! lazy_static! {
-//!    pub static ref REGISTRY: Registry = Registry::new();
-//! }
+//! pub static REGISTRY: LazyLock<Registry> = LazyLock::new( Registry::new );
 //!
 //! prometheus::add_metrics_to_registry(
 //!    &REGISTRY,
@@ -27,28 +26,41 @@
 //! ```
 //!
 
-use ::prometheus::{core::Collector, Registry};
+pub use ::prometheus::TEXT_FORMAT;
+use ::prometheus::{core::Collector, Encoder, Registry, TextEncoder};
+use axum::{
+    http::{Request, Response, StatusCode},
+    Router,
+};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Instant,
+};
+use tower::{Layer, Service};
 
 /// Metrics that can be used to collect data for prometheus.
 pub mod metrics
 {
-    use lazy_static::lazy_static;
     use prometheus::{HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts};
+    use std::sync::LazyLock;
 
-    lazy_static! {
-        pub static ref INCOMING_REQUESTS: IntCounter =
-            IntCounter::new( "incoming_requests", "Incoming Requests" ).expect( "metric can't be created" );
-        pub static ref CONNECTED_CLIENTS: IntGauge =
-            IntGauge::new( "connected_clients", "Connected Clients" ).expect( "metric can't be created" );
-        pub static ref RESPONSE_CODE_COLLECTOR: IntCounterVec = IntCounterVec::new(
-            Opts::new( "response_code", "Response Codes" ),
-            &["env", "statuscode", "type"]
-        )
-        .expect( "metric can't be created" );
-        pub static ref RESPONSE_TIME_COLLECTOR: HistogramVec =
-            HistogramVec::new( HistogramOpts::new( "response_time", "Response Times" ), &["env"] )
-                .expect( "metric can't be created" );
-    }
+    pub static INCOMING_REQUESTS: LazyLock<IntCounter> = LazyLock::new( || {
+        IntCounter::new( "incoming_requests", "Incoming Requests" ).expect( "metric can't be created" )
+    } );
+    pub static CONNECTED_CLIENTS: LazyLock<IntGauge> = LazyLock::new( || {
+        IntGauge::new( "connected_clients", "Connected Clients" ).expect( "metric can't be created" )
+    } );
+    pub static RESPONSE_CODE_COLLECTOR: LazyLock<IntCounterVec> = LazyLock::new( || {
+        IntCounterVec::new( Opts::new( "response_code", "Response Codes" ), &["env", "statuscode", "type"] )
+            .expect( "metric can't be created" )
+    } );
+    pub static RESPONSE_TIME_COLLECTOR: LazyLock<HistogramVec> = LazyLock::new( || {
+        HistogramVec::new( HistogramOpts::new( "response_time", "Response Times" ), &["env"] )
+            .expect( "metric can't be created" )
+    } );
 }
 
 /// Adds a set of metrics to a registry.
@@ -73,3 +85,220 @@ pub fn add_metrics_to_registry( registry: &Registry, metrics: Vec<Box<dyn Collec
         registry.register( metric ).expect( "metric can't be registered" );
     }
 }
+
+/// Encodes all the metrics gathered by a registry in the prometheus text exposition format.
+///
+/// # Arguments
+///
+/// * `registry` - The registry to gather the metrics from.
+///
+/// # Returns
+///
+/// The encoded metrics, or an empty string if they couldn't be encoded.
+///
+#[must_use]
+pub fn encode_metrics( registry: &Registry ) -> String
+{
+    let mut buffer = Vec::new();
+
+    if let Err( err ) = TextEncoder::new().encode( &registry.gather(), &mut buffer )
+    {
+        tracing::error!( "Failed to encode prometheus metrics: {}", err );
+    }
+
+    String::from_utf8( buffer ).unwrap_or_default()
+}
+
+/// Adds the metrics middleware layer to a router.
+///
+/// Every request handled by the router increments [`metrics::INCOMING_REQUESTS`], is tracked by
+/// [`metrics::CONNECTED_CLIENTS`] while in flight, and has its status code and latency recorded on
+/// [`metrics::RESPONSE_CODE_COLLECTOR`] and [`metrics::RESPONSE_TIME_COLLECTOR`].
+///
+/// # Arguments
+///
+/// * `router` - The router to add the layer to.
+/// * `env` - The value of the `env` label ( e.g. the runtime environment ).
+///
+pub fn middleware_http_metrics( router: Router, env: &str ) -> Router { router.layer( MetricsLayer::new( env ) ) }
+
+/// Tower layer that records the [`metrics`] for each request.
+#[derive(Clone)]
+pub struct MetricsLayer
+{
+    env: Arc<str>,
+}
+
+impl MetricsLayer
+{
+    /// Creates a new layer using `env` as the `env` label value.
+    #[must_use]
+    pub fn new( env: &str ) -> Self { Self { env: Arc::from( env ) } }
+}
+
+impl<S> Layer<S> for MetricsLayer
+{
+    type Service = MetricsService<S>;
+
+    fn layer( &self, inner: S ) -> Self::Service
+    {
+        MetricsService {
+            inner,
+            env: self.env.clone(),
+        }
+    }
+}
+
+/// Tower service created by [`MetricsLayer`].
+#[derive(Clone)]
+pub struct MetricsService<S>
+{
+    inner: S,
+    env:   Arc<str>,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for MetricsService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+    type Response = S::Response;
+
+    fn poll_ready( &mut self, cx: &mut Context<'_> ) -> Poll<Result<(), Self::Error>> { self.inner.poll_ready( cx ) }
+
+    fn call( &mut self, request: Request<ReqBody> ) -> Self::Future
+    {
+        // Use the service that was driven to readiness and leave a fresh clone in its place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace( &mut self.inner, clone );
+        let in_flight = InFlightRequest::start( self.env.clone() );
+
+        Box::pin( async move {
+            let result = inner.call( request ).await;
+
+            let status = result
+                .as_ref()
+                .map_or( StatusCode::INTERNAL_SERVER_ERROR, Response::status );
+            in_flight.complete( status );
+
+            result
+        } )
+    }
+}
+
+/// Status code label of the requests whose client disconnected before the response, as used by nginx.
+const CLIENT_CLOSED_REQUEST: &str = "499";
+
+/// Request tracked by [`metrics::CONNECTED_CLIENTS`] until dropped, so that requests whose future is dropped before
+/// completion, e.g. when the client disconnects, are still untracked and recorded.
+struct InFlightRequest
+{
+    env:       Arc<str>,
+    start:     Instant,
+    completed: bool,
+}
+
+impl InFlightRequest
+{
+    fn start( env: Arc<str> ) -> Self
+    {
+        metrics::INCOMING_REQUESTS.inc();
+        metrics::CONNECTED_CLIENTS.inc();
+
+        Self {
+            env,
+            start: Instant::now(),
+            completed: false,
+        }
+    }
+
+    /// Records the latency and status code of the response.
+    fn complete( mut self, status: StatusCode )
+    {
+        self.record( status.as_str(), status_code_type( status ) );
+        self.completed = true;
+    }
+
+    fn record( &self, status: &str, status_type: &str )
+    {
+        // Latency is measured until the response head is ready, streamed bodies are not awaited.
+        metrics::RESPONSE_TIME_COLLECTOR
+            .with_label_values( &[&self.env] )
+            .observe( self.start.elapsed().as_secs_f64() );
+
+        metrics::RESPONSE_CODE_COLLECTOR
+            .with_label_values( &[&self.env, status, status_type] )
+            .inc();
+    }
+}
+
+impl Drop for InFlightRequest
+{
+    fn drop( &mut self )
+    {
+        if !self.completed
+        {
+            self.record( CLIENT_CLOSED_REQUEST, "client_closed" );
+        }
+
+        metrics::CONNECTED_CLIENTS.dec();
+    }
+}
+
+/// Returns the class of a status code, used as the `type` label of the response code metric.
+fn status_code_type( status: StatusCode ) -> &'static str
+{
+    match status.as_u16()
+    {
+        100..=199 => "informational",
+        200..=299 => "success",
+        300..=399 => "redirection",
+        400..=499 => "client_error",
+        _ => "server_error",
+    }
+}
+
+#[cfg( test )]
+#[allow( non_snake_case )]
+mod tests
+{
+    use super::*;
+
+    use std::{convert::Infallible, future};
+    use tower::service_fn;
+
+    // A single test, the metrics are global.
+    #[tokio::test]
+    async fn call__completed_or_dropped__connected_clients_restored_and_status_recorded()
+    {
+        let mut service = MetricsLayer::new( "test" ).layer( service_fn( |request: Request<bool>| async move {
+            if *request.body()
+            {
+                future::pending::<()>().await;
+            }
+
+            Ok::<_, Infallible>( Response::new( () ) )
+        } ) );
+        let response_code = |status: &str, status_type: &str| {
+            metrics::RESPONSE_CODE_COLLECTOR
+                .with_label_values( &["test", status, status_type] )
+                .get()
+        };
+        let connected_clients = metrics::CONNECTED_CLIENTS.get();
+
+        service.call( Request::new( false ) ).await.unwrap();
+
+        assert_eq!( metrics::CONNECTED_CLIENTS.get(), connected_clients );
+        assert_eq!( response_code( "200", "success" ), 1 );
+
+        let pending = service.call( Request::new( true ) );
+        assert_eq!( metrics::CONNECTED_CLIENTS.get(), connected_clients + 1 );
+        drop( pending );
+
+        assert_eq!( metrics::CONNECTED_CLIENTS.get(), connected_clients );
+        assert_eq!( response_code( CLIENT_CLOSED_REQUEST, "client_closed" ), 1 );
+    }
+}